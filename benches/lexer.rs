@@ -0,0 +1,98 @@
+// the matcher pipeline plof started with against the handwritten `Scanner`, over a source
+// both can lex repeated to a few sizes; run with `cargo bench`
+
+extern crate plof;
+
+// kept as it was, names and all, parts of which the bench has no use for
+#[allow(dead_code, clippy::module_inception, clippy::upper_case_acronyms)]
+mod matcher;
+
+use plof::syntax::lexer::{Token, TokenType, TokenPosition, Scanner};
+
+use std::time::{Duration, Instant};
+
+// only what the old matchers lex without panicking: no comments, no escapes but '\n', '\t'
+// and '\"', no two-character comparisons, and words that don't start with 'and' or 'or'
+const SOURCE: &str = r#"
+num (num a, num b) add =
+  a + b * (a - b) / 2 ^ 3 % 4
+
+str (str name) greet =
+  "hello, " ++ name ++ "\n\t\"!\""
+
+table = [
+  name: "plof",
+  raw: r"C:\no\escapes",
+  version: 1.5,
+  parts: [1, 2, 3],
+]
+
+if add 1, 2 > 2 and not_done
+  print greet "you"
+elif 'x' < 'y' or done
+  print table.name
+else
+  return true
+
+unless false
+  print add(10, 20), table.parts, 0 - 3
+"#;
+
+// an old token as the scanner would give it; literals only by type, as the old matchers
+// keep their value rather than what's written
+fn convert(token: &matcher::Token) -> (TokenType, Option<String>) {
+    let token_type = match token.token_type {
+        matcher::TokenType::IntLiteral    => return (TokenType::IntLiteral, None),
+        matcher::TokenType::FloatLiteral  => return (TokenType::FloatLiteral, None),
+        matcher::TokenType::StringLiteral => return (TokenType::StringLiteral, None),
+        matcher::TokenType::BoolLiteral   => TokenType::BoolLiteral,
+        matcher::TokenType::Symbol        => TokenType::Symbol,
+        matcher::TokenType::Operator      => TokenType::Operator,
+        matcher::TokenType::Identifier    => TokenType::Identifier,
+        matcher::TokenType::Keyword       => TokenType::Keyword,
+        matcher::TokenType::Type          => TokenType::Type,
+        ref t => panic!("the old matchers don't give {:?}", t),
+    };
+
+    (token_type, Some(token.content().clone()))
+}
+
+fn same(old: &[matcher::Token], new: &[Token]) -> bool {
+    old.len() == new.len() && old.iter().zip(new.iter()).all(|(old, new)| {
+        let (token_type, content) = convert(old);
+
+        token_type == new.token_type && content.is_none_or(|c| c == new.content())
+    })
+}
+
+// the best of a few runs, to keep the noise out
+fn time<T, F: FnMut() -> Vec<T>>(mut f: F) -> (Duration, Vec<T>) {
+    let mut best   = Duration::MAX;
+    let mut tokens = Vec::new();
+
+    for _ in 0 .. 5 {
+        let start = Instant::now();
+        tokens    = f();
+        best      = best.min(start.elapsed());
+    }
+
+    (best, tokens)
+}
+
+fn main() {
+    println!("{:>10} {:>10} {:>14} {:>14} {:>8}", "bytes", "tokens", "matchers", "scanner", "speedup");
+
+    for &times in &[1, 4, 16, 64] {
+        let input = SOURCE.repeat(times);
+
+        let (matched, old)    = time(|| matcher::lexer(&mut input.chars()).collect());
+        let (scanned, tokens) = time(|| Scanner::new(&input, TokenPosition::default()).collect());
+
+        assert!(same(&old, &tokens), "the scanner and the matchers disagree");
+
+        println!(
+            "{:>10} {:>10} {:>14?} {:>14?} {:>7.1}x",
+            input.len(), tokens.len(), matched, scanned, matched.as_secs_f64() / scanned.as_secs_f64()
+        );
+    }
+}
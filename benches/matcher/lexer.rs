@@ -1,7 +1,6 @@
 use super::Tokenizer;
 use super::matcher::*;
-use super::token::{Token, TokenType, TokenPosition};
-use super::block_tree::{ChunkValue, Branch, Chunk};
+use super::token::{Token, TokenType};
 
 use std::str::Chars;
 use std::rc::Rc;
@@ -10,7 +9,7 @@ pub fn lexer(data: &mut Chars) -> Lexer {
     let tokenizer = Tokenizer::new(data);
     let mut lexer = Lexer::new(tokenizer);
 
-    let symbols = vec![
+    let symbols = [
         "(",
         ")",
         "[",
@@ -26,7 +25,7 @@ pub fn lexer(data: &mut Chars) -> Lexer {
         ".",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let operators = vec![
+    let operators = [
         "++",
         "+",
         "-",
@@ -45,15 +44,15 @@ pub fn lexer(data: &mut Chars) -> Lexer {
         "or",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let keywords = vec![
+    let keywords = [
         "if", "else", "elif", "unless", "return",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let types = vec![
+    let types = [
         "num", "str", "any", "bool",
     ].iter().map(|&x| x.to_string()).collect();
 
-    let boolean = vec![
+    let boolean = [
         "true",
         "false",
     ].iter().map(|&x| x.to_string()).collect();
@@ -82,46 +81,9 @@ pub fn lexer(data: &mut Chars) -> Lexer {
     lexer
 }
 
-pub fn lex_branch(branch: &Branch) -> Branch {
-    let mut lexed_branch = Branch::new(Vec::new());
-    for c in branch.value.iter() {
-        match c.value() {
-            &ChunkValue::Source(ref s) => {
-                let mut line: Vec<Token> = lexer(&mut s.clone().chars()).collect();
-                line.push(Token::new(TokenType::EOL, TokenPosition::default(), "\n".to_owned()));
-
-                let chunk = ChunkValue::Tokens(line);
-                lexed_branch.value.push(Chunk::new(chunk))
-            },
-            &ChunkValue::Block(ref b) => {
-                let chunk = ChunkValue::Block(lex_branch(&b));
-                lexed_branch.value.push(Chunk::new(chunk))
-            },
-            _ => (),
-        }
-    }
-    lexed_branch
-}
-
-pub fn flatten_branch(branch: &Branch) -> Vec<Token> {
-    let mut flat = Vec::new();
-    for c in branch.value.iter() {
-        match c.value() {
-            &ChunkValue::Tokens(ref t) => flat.append(&mut t.clone()),
-            &ChunkValue::Block(ref b)  => flat.push(Token::new(TokenType::Block(flatten_branch(b)), TokenPosition::new(0, 0), "".to_string())),
-            _ => continue,
-        }
-    }
-    flat
-}
-
-pub fn process_branch(branch: &Branch) -> Vec<Token> {
-    flatten_branch(&lex_branch(branch))
-}
-
 pub struct Lexer {
     tokenizer: Tokenizer,
-    matchers: Vec<Rc<Matcher>>,
+    matchers: Vec<Rc<dyn Matcher>>,
 }
 
 #[allow(dead_code)]
@@ -143,11 +105,11 @@ impl Lexer {
         None
     }
 
-    pub fn matchers(&self) -> &Vec<Rc<Matcher>> {
+    pub fn matchers(&self) -> &Vec<Rc<dyn Matcher>> {
         &self.matchers
     }
 
-    pub fn matchers_mut(&mut self) -> &mut Vec<Rc<Matcher>> {
+    pub fn matchers_mut(&mut self) -> &mut Vec<Rc<dyn Matcher>> {
         &mut self.matchers
     }
 }
@@ -159,12 +121,7 @@ impl Iterator for Lexer {
         let token = self.match_token().unwrap();
         match token.token_type {
             TokenType::EOF => None,
-            TokenType::Whitespace => {
-                match self.next() {
-                    Some(t) => Some(t),
-                    None => None,
-                }
-            }
+            TokenType::Whitespace => self.next(),
             _ => Some(token),
         }
     }
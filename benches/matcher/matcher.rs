@@ -6,8 +6,8 @@ macro_rules! token {
         token!($tokenizer , TokenType::$token_type, $accum)
     }};
     ($tokenizer:expr, $token_type:expr, $accum:expr) => {{
-        let tokenizer = $tokenizer as &$crate::plof::syntax::lexer::Tokenizer;
-        let token_type = $token_type as $crate::plof::syntax::lexer::token::TokenType;
+        let tokenizer = $tokenizer as &$crate::matcher::Tokenizer;
+        let token_type = $token_type as $crate::matcher::token::TokenType;
         Some(Token::new(token_type, tokenizer.last_position(), $accum))
     }};
 }
@@ -43,20 +43,20 @@ impl Matcher for IntLiteralMatcher {
             Some(&'+') => Some(true),
             _          => None,
         };
-        if let Some(_) = prefix {
+        if prefix.is_some() {
             tokenizer.advance(1)
         };
-        while !tokenizer.end() && tokenizer.peek().unwrap().is_digit(10) {
+        while !tokenizer.end() && tokenizer.peek().unwrap().is_ascii_digit() {
             accum.push(tokenizer.next().unwrap());
         }
         if !accum.is_empty() {
             let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<i64>() {
                     Ok(result) => format!("-{}", result),
                     Err(error) => panic!("unable to parse int-literal: {}", error)
                 }
             } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<u64>() {
                     Ok(result) => result.to_string(),
                     Err(error) => panic!("unable to parse int-literal: {}", error)
                 }
@@ -79,12 +79,12 @@ impl Matcher for FloatLiteralMatcher {
             Some(&'+') => Some(true),
             _          => None,
         };
-        if let Some(_) = prefix {
+        if prefix.is_some() {
             tokenizer.advance(1)
         };
 
         let curr = tokenizer.next().unwrap();
-        if curr.is_digit(10) {
+        if curr.is_ascii_digit() {
             accum.push(curr)
         } else if curr == '.' {
             accum.push_str("0.")
@@ -93,7 +93,7 @@ impl Matcher for FloatLiteralMatcher {
         }
         while !tokenizer.end() {
             let current = *tokenizer.peek().unwrap();
-            if !current.is_whitespace() && current.is_digit(10) || current == '.' {
+            if !current.is_whitespace() && current.is_ascii_digit() || current == '.' {
                 if current == '.' && accum.contains('.') {
                     panic!("illegal decimal point")
                 }
@@ -103,7 +103,7 @@ impl Matcher for FloatLiteralMatcher {
             }
         }
 
-        if accum == "0.".to_owned() {
+        if accum == "0." {
             None
         } else if accum.contains('.') {
 
@@ -122,12 +122,12 @@ impl Matcher for FloatLiteralMatcher {
             token!(tokenizer, FloatLiteral, literal)
         } else {
             let literal: String = if Some(false) == prefix {
-                match i64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<i64>() {
                     Ok(result) => format!("-{}", result),
                     Err(error) => panic!("unable to parse int-literal: {}", error)
                 }
             } else {
-                match u64::from_str_radix(accum.as_str(), 10) {
+                match accum.parse::<u64>() {
                     Ok(result) => result.to_string(),
                     Err(error) => panic!("unable to parse int-literal: {}", error)
                 }
@@ -143,10 +143,10 @@ pub struct StringLiteralMatcher;
 impl Matcher for StringLiteralMatcher {
     fn try_match(&self, tokenizer: &mut Tokenizer) -> Option<Token> {
         let mut raw_marker = false;
-        let delimeter  = match tokenizer.peek().unwrap() {
-            &'"'  => Some('"'),
-            &'\'' => Some('\''),
-            &'r' => match *tokenizer.peek_n(1).unwrap() {
+        let delimeter  = match *tokenizer.peek().unwrap() {
+            '"'  => Some('"'),
+            '\'' => Some('\''),
+            'r' => match *tokenizer.peek_n(1).unwrap() {
                 c @ '"' | c @ '\'' => {
                     raw_marker = true;
                     tokenizer.advance(1); // Skips prefix
@@ -182,12 +182,12 @@ impl Matcher for StringLiteralMatcher {
                     );
                     found_escape = false
                 } else {
-                    match tokenizer.peek().unwrap() {
-                        &'\\' => {
+                    match *tokenizer.peek().unwrap() {
+                        '\\' => {
                             tokenizer.next();
                             found_escape = true
                         },
-                        &c if &c == &delimeter => break,
+                        c if c == delimeter => break,
                         _ => string.push(tokenizer.next().unwrap()),
                     }
                 }
@@ -206,8 +206,8 @@ pub struct ConstantMatcher {
 impl ConstantMatcher {
     pub fn new(token_type: TokenType, constants: Vec<String>) -> Self {
         ConstantMatcher {
-            token_type: token_type,
-            constants: constants,
+            token_type,
+            constants,
         }
     }
 }
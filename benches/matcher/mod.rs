@@ -0,0 +1,9 @@
+pub mod token;
+pub mod tokenizer;
+pub mod matcher;
+pub mod lexer;
+
+pub use self::token::*;
+pub use self::tokenizer::*;
+pub use self::matcher::*;
+pub use self::lexer::*;
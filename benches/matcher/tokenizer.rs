@@ -34,7 +34,7 @@ impl Iterator for Tokenizer {
 
 #[allow(dead_code)]
 impl Tokenizer {
-    pub fn new(items: &mut Iterator<Item = char>) -> Tokenizer {
+    pub fn new(items: &mut dyn Iterator<Item = char>) -> Tokenizer {
         Tokenizer {
             index:     0,
             pos:       TokenPosition::default(),
@@ -105,7 +105,7 @@ impl Tokenizer {
         self.peek_snapshot().unwrap().pos
     }
 
-    pub fn try_match_token(&mut self, matcher: &Matcher) -> Option<Token> {
+    pub fn try_match_token(&mut self, matcher: &dyn Matcher) -> Option<Token> {
         if self.end() {
             return Some(Token::new(TokenType::EOF,
                                    TokenPosition::new(self.index, self.index),
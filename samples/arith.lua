@@ -1,2 +1,2 @@
 local a = ((((1)+(3)))+(((((10)*(10)))*(19))));
-print((a))
\ No newline at end of file
+print((a))
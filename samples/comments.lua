@@ -0,0 +1,7 @@
+local tilde = "~ stays in strings";
+function twice(a)
+return (((((a)*(2)))+(0)));
+end
+if (((twice((2)))==(4))) then
+print((tilde))
+end
@@ -0,0 +1,21 @@
+local a = 10;
+if (((a)==(10))) then
+print(("ten"))
+elseif (((a)>(10))) then
+print(("big"))
+else
+print(("small"))
+end
+if not (((a)==(1))) then
+print(("not one"))
+end
+function size(n)
+if (((n)>(100))) then
+return ("big");
+elseif (((n)>(10))) then
+return ("medium");
+else
+return ("small");
+end
+end
+print((size((a))))
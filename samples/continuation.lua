@@ -0,0 +1,6 @@
+local total = ((1)+(((2)*(3))));
+print((total),(((total)+(1))))
+local big = ((((total)>(5)))and(((total)<(10))));
+if (((big)and(((total)~=(8))))) then
+print(("big"))
+end
@@ -1,5 +1,7 @@
-local add = function (a,b)return (((a)+(b)));
+local add = function (a,b)
+return (((a)+(b)));
 end;
-function (a)local ba = 123;
-return (123);
-end
\ No newline at end of file
+function (a)
+local ba = ((a)+(123));
+return (ba);
+end
@@ -0,0 +1,7 @@
+if (true) then
+print(("("),("["))
+print(("still in the block"))
+end
+local total = ((((1)+(2)))+(3));
+local dict = {a = "(",b = total,};
+print((total))
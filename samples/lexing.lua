@@ -0,0 +1,19 @@
+local a = 10;
+local b = 2;
+local s = "ab";
+print((((a)==(b))),(((a)~=(b))),(((a)>=(b))),(((a)<=(b))),(((a)>(b))),(((a)<(b))))
+print((((a)+(b))),(((a)-(b))),(((a)*(b))),(((a)/(b))),(((a)%(b))),(((a)^(b))))
+print((((s)..(s))),((# (s))),((not (((a)==(b))))))
+print((((((((a)>=(b)))and(((b)<=(a)))))or((not (((a)~=(b))))))))
+function first(x)
+return (x);
+end
+for i = (1), (3) do
+print((first((i))))
+end
+local android = 1;
+local iffy = 2;
+local orange = 3;
+local notable = 4;
+print((((((((android)+(iffy)))+(orange)))+(notable))))
+print((((a)>=(b))))
@@ -0,0 +1,12 @@
+local i = 0;
+while (((i)<(3))) do
+print((i))
+i = ((i)+(1));
+end
+for n = (1), (10) do
+print((n))
+end
+local t = {a = "b",};
+for k, v in pairs(t) do
+print((k),(v))
+end
@@ -1,2 +1,4 @@
-function idk(a)return (((a)+(10)));
-endprint((idk((1))))
\ No newline at end of file
+function idk(a)
+return (((a)+(10)));
+end
+print((idk((1))))
@@ -0,0 +1,4 @@
+print((255),(10),(15),(1000000))
+print((0.0015),(20000000000),(6.02e23),(0.1))
+local a = -1;
+print((((a)-(1))),(((2)^(-1))))
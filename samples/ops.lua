@@ -0,0 +1,13 @@
+local a = 10;
+local b = 2;
+local c = 3;
+print((((((a)-(b)))-(c))))
+print((((((((a)/(b)))/(c)))%(4))))
+print((((a)^(((b)^(c))))))
+print(((- (((a)^(2))))))
+print((((((((a)-(b)))*(c)))+((- (b))))))
+print((((((((((a)+(b)))==(12)))and(((b)<(c)))))or(false))))
+print(((((not (a)))==(b))))
+local s = "hello";
+print(((not (((a)==(b))))),((not (true))),((((# (s)))+((# ({1,2,}))))))
+print((((s)..(((", ")..(((a)+(b))))))))
@@ -0,0 +1,13 @@
+print(("quote \" and apostrophe ' inside"))
+print(("single 'quoted' \"string\""))
+print(("backslash \\ tab \t newline \n return \r end"))
+print(("C:\\path\\to\\file"))
+print(("no \\n escapes \\t here"))
+local name = "plof";
+local age = 3;
+print((("hello " .. tostring(name) .. ", you are " .. tostring(((age)+(1))))))
+print((((("nested ")..(name)) .. " {not interpolated}")))
+local both = (tostring(age) .. tostring(name));
+print(("raw {name} stays"))
+local usage = ("usage: " .. tostring(name) .. " <file>\n  compiles <file> to lua, \"quotes\" welcome\n");
+print((usage),("raw\n  \\n"))
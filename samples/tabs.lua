@@ -0,0 +1,10 @@
+function clamp(n)
+if (((n)>(10))) then
+return (10);
+end
+if (((n)<(0))) then
+return (0);
+end
+return (n);
+end
+print((clamp((12))),(clamp((-3))),(clamp((5))))
@@ -1,3 +1,3 @@
 local a = 123;
-local a = 321;
-print((a))
\ No newline at end of file
+a = 321;
+print((a))
@@ -0,0 +1,189 @@
+use plof::syntax::TAB_WIDTH;
+
+pub const USAGE: &str = r"the plof language
+
+usage:
+  plof [command] [options] <file or folder>...
+
+commands:
+  build     compiles to lua, next to each source unless told where (the default)
+  check     reports every error, without writing anything
+  run       compiles and runs the result with lua
+  fmt       formats sources in place
+  ast       prints the syntax tree
+  tokens    prints the tokens
+
+options:
+  -o, --out-dir <dir>    where 'build' writes, keeping the layout of a folder
+  -t, --target <target>  what to compile to: lua
+  -W, --deny-warnings    fails on warnings like on errors
+      --check            with 'fmt', only tells which files would change
+      --tab-width <n>    how many columns a tab indents to, 4 by default
+  -q, --quiet            prints nothing but diagnostics and what was asked for
+  -v, --verbose          tells more about what it's doing
+  -h, --help             prints this
+";
+
+// nothing went wrong
+pub const EXIT_OK:      i32 = 0;
+// a source didn't compile, or was left unformatted with 'fmt --check', or couldn't be read or written
+pub const EXIT_FAILURE: i32 = 1;
+// the command line didn't make sense
+pub const EXIT_USAGE:   i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Build,
+    Check,
+    Run,
+    Fmt,
+    Ast,
+    Tokens,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "build"  => Some(Command::Build),
+            "check"  => Some(Command::Check),
+            "run"    => Some(Command::Run),
+            "fmt"    => Some(Command::Fmt),
+            "ast"    => Some(Command::Ast),
+            "tokens" => Some(Command::Tokens),
+            _        => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Lua,
+}
+
+impl Target {
+    fn from_name(name: &str) -> Option<Target> {
+        match name {
+            "lua" => Some(Target::Lua),
+            _     => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            Target::Lua => "lua",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command:       Command,
+    pub paths:         Vec<String>,
+    pub out_dir:       Option<String>,
+    pub target:        Target,
+    pub deny_warnings: bool,
+    pub check:         bool,
+    pub tab_width:     usize,
+    pub verbosity:     Verbosity,
+}
+
+// what the command line asked for: options to go ahead with, or a request for help
+pub enum Parsed {
+    Run(Options),
+    Help,
+}
+
+// the value after a flag taking one
+fn value<I: Iterator<Item = String>>(flag: &str, args: &mut I) -> Result<String, String> {
+    args.next().ok_or_else(|| format!("'{}' needs a value", flag))
+}
+
+pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Parsed, String> {
+    let mut options = Options {
+        command:       Command::Build,
+        paths:         Vec::new(),
+        out_dir:       None,
+        target:        Target::Lua,
+        deny_warnings: false,
+        check:         false,
+        tab_width:     TAB_WIDTH,
+        verbosity:     Verbosity::Normal,
+    };
+
+    let mut flags = true;
+    let mut first = true;
+
+    while let Some(arg) = args.next() {
+        // a command only goes first, so a file called 'run' can still be built with 'plof build run'
+        if first {
+            first = false;
+
+            if arg == "help" {
+                return Ok(Parsed::Help)
+            }
+
+            if let Some(command) = Command::from_name(&arg) {
+                options.command = command;
+                continue
+            }
+        }
+
+        if !flags || !arg.starts_with('-') {
+            options.paths.push(arg);
+            continue
+        }
+
+        match arg.as_str() {
+            "--" => flags = false,
+
+            "-h" | "--help" => return Ok(Parsed::Help),
+
+            "-o" | "--out-dir" => options.out_dir = Some(value(&arg, &mut args)?),
+
+            "-t" | "--target" => {
+                let name = value(&arg, &mut args)?;
+
+                options.target = Target::from_name(&name).ok_or_else(|| format!("unknown target '{}', the only one is 'lua'", name))?
+            },
+
+            "-W" | "--deny-warnings" => options.deny_warnings = true,
+
+            "--check" => options.check = true,
+
+            "--tab-width" => {
+                let width = value(&arg, &mut args)?;
+
+                options.tab_width = match width.parse() {
+                    Ok(n) if n > 0 => n,
+                    _              => return Err(format!("'{}' isn't a tab width, which is a number above 0", width)),
+                }
+            },
+
+            "-q" | "--quiet"   => options.verbosity = Verbosity::Quiet,
+            "-v" | "--verbose" => options.verbosity = Verbosity::Verbose,
+
+            _ => return Err(format!("unknown option '{}'", arg)),
+        }
+    }
+
+    if options.paths.is_empty() {
+        return Err("expected a file or folder".to_owned())
+    }
+
+    if options.check && options.command != Command::Fmt {
+        return Err("'--check' only goes with 'fmt'".to_owned())
+    }
+
+    if options.out_dir.is_some() && options.command != Command::Build {
+        return Err("'--out-dir' only goes with 'build'".to_owned())
+    }
+
+    Ok(Parsed::Run(options))
+}
@@ -0,0 +1,5 @@
+#![allow(clippy::module_inception, clippy::result_large_err)]
+
+mod plof;
+
+pub use plof::syntax;
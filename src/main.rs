@@ -1,21 +1,23 @@
-mod plof;
+extern crate plof;
+
+mod cli;
 
 use plof::syntax;
-use syntax::lexer::{BlockTree, process_branch};
-use syntax::parser::{Traveler, Parser, Type};
-use syntax::{SymTab, Env};
+use syntax::lexer::{Layout, Token, TokenType};
+use syntax::parser::{Traveler, Parser, Statement, Type, visit_statements, unused_names};
+use syntax::{SymTab, Env, Renderer, Diagnostic, Cst, E_INTERNAL, lexical_errors, format};
+
+use cli::{Command, Options, Parsed, Verbosity, EXIT_OK, EXIT_FAILURE, EXIT_USAGE};
 
 use std::rc::Rc;
 
 use std::io::prelude::*;
-use std::error::Error;
+use std::io::IsTerminal;
 
 use std::fs;
-use std::fs::File;
 use std::env;
-use std::path::Path;
-
-use std::fs::metadata;
+use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
 
 fn add_global(sym: &SymTab, env: &Env, name: &str, t: Type) {
     let i = sym.add_name(name);
@@ -23,118 +25,345 @@ fn add_global(sym: &SymTab, env: &Env, name: &str, t: Type) {
     if i >= env.size() {
         env.grow();
     }
-    
-    env.set_type(i, 0, t);
+
+    env.set_type(i, 0, t).unwrap();
 }
 
 fn add_lua_standard(sym: &SymTab, env: &Env) {
-    add_global(sym, env, "print", Type::Lambda(Rc::new(vec![Type::Nil, Type::Any])));
+    add_global(sym, env, "print", Type::Lambda(Rc::new(vec![Type::Nil, Type::Many(Rc::new(Type::Any))])));
     add_global(sym, env, "tostring", Type::Str);
 }
 
-fn do_path(path: &str) {
-    let meta = metadata(path).unwrap();
-    
-    if meta.is_file() {
-        file(path)
-    } else {
-        let paths = fs::read_dir(path).unwrap();
-        
-        for path in paths {
-            let path = format!("{}", path.unwrap().path().display());
-            let split: Vec<&str> = path.split(".").collect();
-
-            match split.get(1) {
-                Some(n) if *n == "plof" => (),
-                _ => continue,
-            }
+// colored diagnostics only when a person is looking, and not told otherwise
+fn use_color() -> bool {
+    std::io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+// what's happening, unless told to be quiet, or things only worth telling when asked to be verbose
+fn say(options: &Options, verbosity: Verbosity, what: &str) {
+    if options.verbosity >= verbosity {
+        eprintln!("{}", what)
+    }
+}
+
+// prints the diagnostics, and tells whether they fail the source
+fn report(renderer: &Renderer, diagnostics: &[Diagnostic], options: &Options) -> bool {
+    for d in diagnostics.iter() {
+        eprintln!("{}\n", renderer.render(d))
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error() || options.deny_warnings).count();
+
+    match errors {
+        0 => (),
+        1 => eprintln!("error: aborting due to previous error"),
+        n => eprintln!("error: aborting due to {} previous errors", n),
+    }
 
-            do_path(&format!("{}", path))
+    errors > 0
+}
+
+// the sources at `path`: the file itself, or every .plof file in the folder and those in it
+fn sources(path: &Path, found: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    if !fs::metadata(path)?.is_dir() {
+        found.push(path.to_owned());
+        return Ok(())
+    }
+
+    let mut entries = fs::read_dir(path)?.map(|e| e.map(|e| e.path())).collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for entry in entries {
+        if entry.is_dir() || entry.extension().is_some_and(|e| e == "plof") {
+            sources(&entry, found)?
         }
     }
+
+    Ok(())
 }
 
-fn file(path: &str) {
-    let path = Path::new(path);
-    let display = path.display();
-
-    let mut file = match File::open(&path) {
-        Err(why) => panic!("failed to open {}: {}", display, why.description()),
-        Ok(file) => file,
-    };    
-
-    let mut s = String::new();
-        
-    match file.read_to_string(&mut s) {
-        Err(why) => panic!("failed to read {}: {}", display,  why.description()),
-        Ok(_)    => {
-            println!("building: {}", display);
-            
-            let mut blocks = BlockTree::new(&s, 0);
-            let indents    = blocks.indents();
-
-            let root = blocks.tree(&indents);
-            let done = process_branch(&root);
-
-            let mut parser = Parser::new(Traveler::new(done.clone()));
-
-            let symtab = Rc::new(syntax::SymTab::new_global());
-            let env    = Rc::new(syntax::Env::new_global());
-
-            add_lua_standard(&symtab, &env);
-
-            match parser.parse() {
-                Err(why)  => println!("error: {}", why),
-                Ok(stuff) => {                    
-                    for s in stuff.iter() {
-                        match s.visit(&symtab, &env) {
-                            Ok(()) => (),
-                            Err(e) => {
-                                println!("{}", e);
-                                return
-                            },
-                        }
-                    }
-                    
-                    let mut output = String::new();
-                    
-                    for s in stuff.iter() {
-                        output.push_str(&format!("{}", s))
-                    }
-                    
-                    let split_name = path.file_name().unwrap().to_str().unwrap().split(".");
-                    let split: Vec<&str> = split_name.collect();
-                    
-                    let parent_path = match path.parent() {
-                        Some(p) => match p.file_name() {
-                            Some(path) => path.to_str().unwrap(),
-                            None       => ".",
-                        },
-                        None    => ".",
-                    };
-
-                    let output_name = format!("{}/{}.lua", parent_path, split.get(0).unwrap());
-
-                    let mut output_file = File::create(output_name).unwrap();
-                    match output_file.write_all(output.as_bytes()) {
-                        Ok(_)    => (),
-                        Err(why) => println!("{}", why.description())
-                    }
-                },
-            }
+// where a source compiles to: next to it, or under the output folder as it is under the
+// folder it was found in
+fn output_path(root: &Path, path: &Path, options: &Options) -> PathBuf {
+    let output = path.with_extension(options.target.extension());
+
+    match options.out_dir {
+        None          => output,
+        Some(ref dir) => {
+            let relative = match output.strip_prefix(root) {
+                Ok(relative) if root != path => relative.to_owned(),
+                _                            => PathBuf::from(output.file_name().unwrap()),
+            };
+
+            Path::new(dir).join(relative)
+        },
+    }
+}
+
+// a source's statements, lexed with trivia and parsed, or nothing having reported why not
+fn parse(source: &str, renderer: &Renderer, options: &Options) -> Option<(Vec<Statement>, Vec<Diagnostic>)> {
+    let cst = Cst::new(Layout::new(source).with_tab_width(options.tab_width).with_trivia());
+
+    let lexical = lexical_errors(&cst.tokens);
+
+    if report(renderer, &lexical, options) {
+        return None
+    }
+
+    let (statements, errors) = Parser::new(Traveler::new(&cst.tokens)).parse();
+
+    if errors.iter().any(|d| d.is_error()) {
+        report(renderer, &errors, options);
+        return None
+    }
+
+    Some((statements, errors))
+}
+
+// the lua a source compiles to, or nothing having reported why it doesn't
+fn compile(source: &str, renderer: &Renderer, options: &Options) -> Option<String> {
+    let (statements, mut diagnostics) = parse(source, renderer, options)?;
+
+    let symtab = Rc::new(SymTab::new_global());
+    let env    = Rc::new(Env::new_global());
+
+    add_lua_standard(&symtab, &env);
+
+    if let Err(errors) = visit_statements(&statements, &symtab, &env) {
+        diagnostics.extend(errors)
+    }
+
+    diagnostics.extend(unused_names(&statements));
+
+    if report(renderer, &diagnostics, options) {
+        return None
+    }
+
+    let mut output = String::new();
+
+    for s in statements.iter() {
+        output.push_str(&format!("{}\n", s))
+    }
+
+    Some(output)
+}
+
+fn build(root: &Path, path: &Path, source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    say(options, Verbosity::Normal, &format!("building: {}", path.display()));
+
+    let output = match compile(source, renderer, options) {
+        Some(output) => output,
+        None         => return EXIT_FAILURE,
+    };
+
+    let output_path = output_path(root, path, options);
+
+    let written = match output_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent).and_then(|_| fs::write(&output_path, output)),
+        _                                              => fs::write(&output_path, output),
+    };
+
+    match written {
+        Ok(_) => {
+            say(options, Verbosity::Verbose, &format!("wrote: {}", output_path.display()));
+            EXIT_OK
+        },
+        Err(why) => {
+            eprintln!("error: failed to write {}: {}", output_path.display(), why);
+            EXIT_FAILURE
+        },
+    }
+}
+
+fn check(path: &Path, source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    say(options, Verbosity::Normal, &format!("checking: {}", path.display()));
+
+    match compile(source, renderer, options) {
+        Some(_) => EXIT_OK,
+        None    => EXIT_FAILURE,
+    }
+}
+
+// runs the compiled source with `lua`, reading it from stdin; exits as lua did
+fn run(path: &Path, source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    say(options, Verbosity::Verbose, &format!("running: {}", path.display()));
+
+    let output = match compile(source, renderer, options) {
+        Some(output) => output,
+        None         => return EXIT_FAILURE,
+    };
+
+    let lua = process::Command::new("lua").arg("-").stdin(Stdio::piped()).spawn();
+
+    let mut lua = match lua {
+        Ok(lua)  => lua,
+        Err(why) => {
+            eprintln!("error: failed to run lua: {}", why);
+            return EXIT_FAILURE
+        },
+    };
+
+    // lua reads the whole chunk before running it, which ends once this is dropped
+    if let Some(mut stdin) = lua.stdin.take() {
+        if let Err(why) = stdin.write_all(output.as_bytes()) {
+            eprintln!("error: failed to run lua: {}", why);
+        }
+    }
+
+    match lua.wait() {
+        Ok(status) if status.success() => EXIT_OK,
+        Ok(status) => status.code().unwrap_or(EXIT_FAILURE),
+        Err(why)   => {
+            eprintln!("error: failed to run lua: {}", why);
+            EXIT_FAILURE
+        },
+    }
+}
+
+// the significant tokens of a source, text and all, for telling if two mean the same
+fn meaning(source: &str, tab_width: usize) -> Vec<(TokenType, String)> {
+    Layout::new(source).with_tab_width(tab_width).filter(|t| t.token_type != TokenType::Indent).map(|t| (t.token_type.clone(), t.content().to_owned())).collect()
+}
+
+// formats a file in place, or with '--check' only tells if it would
+fn fmt(path: &Path, source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    let cst = Cst::new(Layout::new(source).with_tab_width(options.tab_width).with_trivia());
+
+    if report(renderer, &lexical_errors(&cst.tokens), options) {
+        return EXIT_FAILURE
+    }
+
+    let (_, errors) = Parser::new(Traveler::new(&cst.tokens)).parse();
+
+    if report(renderer, &errors, options) {
+        return EXIT_FAILURE
+    }
+
+    let formatted = format(&cst);
+
+    // only whitespace is meant to change, so anything else is a bug in the formatter
+    if meaning(&formatted, options.tab_width) != meaning(source, options.tab_width) {
+        report(renderer, &[Diagnostic::error(E_INTERNAL, "formatting would change what this file means")], options);
+        return EXIT_FAILURE
+    }
+
+    if formatted == source {
+        say(options, Verbosity::Verbose, &format!("unchanged: {}", path.display()));
+        return EXIT_OK
+    }
+
+    if options.check {
+        println!("would reformat: {}", path.display());
+        return EXIT_FAILURE
+    }
+
+    match fs::write(path, formatted) {
+        Ok(_) => {
+            say(options, Verbosity::Normal, &format!("formatted: {}", path.display()));
+            EXIT_OK
+        },
+        Err(why) => {
+            eprintln!("error: failed to write {}: {}", path.display(), why);
+            EXIT_FAILURE
+        },
+    }
+}
+
+fn ast(source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    match parse(source, renderer, options) {
+        Some((statements, diagnostics)) => {
+            // a closed stdout, like that of 'plof ast a.plof | head', only means nobody's reading
+            let _ = writeln!(std::io::stdout(), "{:#?}", statements);
+
+            if report(renderer, &diagnostics, options) { EXIT_FAILURE } else { EXIT_OK }
+        },
+        None => EXIT_FAILURE,
+    }
+}
+
+// every token, one a line; whitespace and comments too when verbose
+fn tokens(source: &str, renderer: &Renderer, options: &Options) -> i32 {
+    let mut layout = Layout::new(source).with_tab_width(options.tab_width);
+
+    if options.verbosity == Verbosity::Verbose {
+        layout = layout.with_trivia()
+    }
+
+    let tokens: Vec<Token> = layout.collect();
+
+    let mut out = std::io::stdout().lock();
+
+    for t in tokens.iter() {
+        let at   = format!("{}:{}", t.span.start.line, t.span.start.col + 1);
+        let kind = format!("{}", t.token_type);
+
+        let written = match t.token_type {
+            TokenType::Whitespace => writeln!(out, "{:<10} {:<20} {:?}", at, kind, t.content()),
+            _                     => writeln!(out, "{:<10} {:<20} {}", at, kind, t),
+        };
+
+        if written.is_err() {
+            break
         }
     }
+
+    if report(renderer, &lexical_errors(&tokens), options) { EXIT_FAILURE } else { EXIT_OK }
 }
 
 fn main() {
-    let mut args = env::args();
-    match args.nth(1) {
-        Some(a) => do_path(&a),
-        None    => println!(r"
-the plof language
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Parsed::Run(options)) => options,
+        Ok(Parsed::Help)         => {
+            print!("{}", cli::USAGE);
+            return
+        },
+        Err(why) => {
+            eprintln!("error: {}\n\nsee 'plof --help' for how to use it", why);
+            process::exit(EXIT_USAGE)
+        },
+    };
+
+    let mut status = EXIT_OK;
+
+    for root in options.paths.iter() {
+        let root = Path::new(root);
+
+        let mut found = Vec::new();
+
+        if let Err(why) = sources(root, &mut found) {
+            eprintln!("error: failed to read {}: {}", root.display(), why);
+            status = EXIT_FAILURE;
+            continue
+        }
 
-usage:
-  plof <file> or <folder>
-        "),
+        for path in found.iter() {
+            let source = match fs::read_to_string(path) {
+                Ok(source) => source,
+                Err(why)   => {
+                    eprintln!("error: failed to read {}: {}", path.display(), why);
+                    status = EXIT_FAILURE;
+                    continue
+                },
+            };
+
+            let name     = format!("{}", path.display());
+            let renderer = Renderer::new(&name, &source, use_color());
+
+            let code = match options.command {
+                Command::Build  => build(root, path, &source, &renderer, &options),
+                Command::Check  => check(path, &source, &renderer, &options),
+                Command::Run    => run(path, &source, &renderer, &options),
+                Command::Fmt    => fmt(path, &source, &renderer, &options),
+                Command::Ast    => ast(&source, &renderer, &options),
+                Command::Tokens => tokens(&source, &renderer, &options),
+            };
+
+            // the first failure is what it exits with, but every source gets its turn
+            if status == EXIT_OK {
+                status = code
+            }
+        }
     }
+
+    process::exit(status)
 }
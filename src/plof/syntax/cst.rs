@@ -0,0 +1,283 @@
+use std::fmt;
+use std::mem;
+use std::ops::Range;
+
+use super::lexer::{Token, TokenType, Span};
+
+// the whitespace and comments around a token; what follows it on its own line trails
+// it, and everything else leads the next token with text
+#[derive(Debug, Clone, Default)]
+pub struct Trivia<'a> {
+    pub leading:  Vec<Token<'a>>,
+    pub trailing: Vec<Token<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NodeKind {
+    File,
+    Block, // an Indent, the lines and blocks in it, and its Dedent
+    Line,  // a logical line, up to and including its EOL
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub kind:     NodeKind,
+    pub span:     Span,
+    pub tokens:   Range<usize>, // where its tokens are in the tree's
+    pub children: Vec<Node>,
+}
+
+impl Node {
+    fn new(kind: NodeKind, start: usize) -> Node {
+        Node {
+            kind,
+            span:     Span::default(),
+            tokens:   start .. start,
+            children: Vec::new(),
+        }
+    }
+
+    fn end(mut self, end: usize, tokens: &[Token]) -> Node {
+        self.tokens.end = end;
+
+        if end > self.tokens.start {
+            self.span = tokens[self.tokens.start].span.to(tokens[end - 1].span)
+        }
+
+        self
+    }
+}
+
+// a lossless concrete syntax tree: the tokens of a `Layout`, each with its trivia, and
+// the lines and blocks they make up; the parser reads its tokens, and printing it gives
+// back the source it came from to the byte
+#[derive(Debug)]
+pub struct Cst<'a> {
+    pub tokens: Vec<Token<'a>>,
+    pub trivia: Vec<Trivia<'a>>, // one for every token
+    pub root:   Node,
+}
+
+// the tokens the layout makes up, which have no text of their own
+pub fn is_layout(token: &Token) -> bool {
+    matches!(token.token_type, TokenType::Indent | TokenType::Dedent | TokenType::EOL | TokenType::EOF)
+}
+
+fn is_trivia(token: &Token) -> bool {
+    matches!(token.token_type, TokenType::Whitespace | TokenType::Comment)
+}
+
+impl<'a> Cst<'a> {
+    // `tokens` being those of a `Layout` with trivia
+    pub fn new<I: IntoIterator<Item = Token<'a>>>(tokens: I) -> Cst<'a> {
+        let mut significant = Vec::new();
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        let mut pending  = Vec::new();
+        let mut trailing: Option<usize> = None; // the token that trivia on the same line goes with
+
+        for token in tokens {
+            if is_trivia(&token) {
+                if token.token_type == TokenType::Whitespace && token.content().contains('\n') {
+                    trailing = None
+                }
+
+                match trailing {
+                    Some(i) => trivia[i].trailing.push(token),
+                    None    => pending.push(token),
+                }
+
+                continue
+            }
+
+            // made up tokens take no trivia, bar the end taking whatever is left
+            let leading = if !is_layout(&token) || token.token_type == TokenType::EOF {
+                mem::take(&mut pending)
+            } else {
+                Vec::new()
+            };
+
+            if !is_layout(&token) {
+                trailing = Some(significant.len())
+            }
+
+            significant.push(token);
+            trivia.push(Trivia {
+                leading,
+                trailing: Vec::new(),
+            })
+        }
+
+        let root = Cst::lines(&significant);
+
+        Cst {
+            tokens: significant,
+            trivia,
+            root,
+        }
+    }
+
+    fn lines(tokens: &[Token]) -> Node {
+        let mut stack = vec![Node::new(NodeKind::File, 0)];
+        let mut line  = None;
+
+        for (i, token) in tokens.iter().enumerate() {
+            match token.token_type {
+                TokenType::Indent => stack.push(Node::new(NodeKind::Block, i)),
+
+                TokenType::Dedent if stack.len() > 1 => {
+                    let block = stack.pop().unwrap().end(i + 1, tokens);
+                    stack.last_mut().unwrap().children.push(block)
+                },
+
+                TokenType::Dedent | TokenType::EOF => (),
+
+                _ => {
+                    let start = *line.get_or_insert(i);
+
+                    if token.token_type == TokenType::EOL {
+                        let node = Node::new(NodeKind::Line, start).end(i + 1, tokens);
+                        stack.last_mut().unwrap().children.push(node);
+
+                        line = None
+                    }
+                },
+            }
+        }
+
+        // blocks left open run to the end
+        while stack.len() > 1 {
+            let block = stack.pop().unwrap().end(tokens.len(), tokens);
+            stack.last_mut().unwrap().children.push(block)
+        }
+
+        stack.pop().unwrap().end(tokens.len(), tokens)
+    }
+}
+
+impl<'a> fmt::Display for Cst<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (token, trivia) in self.tokens.iter().zip(self.trivia.iter()) {
+            for t in trivia.leading.iter() {
+                write!(f, "{}", t.content())?
+            }
+
+            if !is_layout(token) {
+                write!(f, "{}", token.content())?
+            }
+
+            for t in trivia.trailing.iter() {
+                write!(f, "{}", t.content())?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::lexer::{Layout, TokenPosition};
+
+    use std::fs;
+
+    fn cst(source: &str) -> Cst<'_> {
+        Cst::new(Layout::new(source).with_trivia())
+    }
+
+    // every token with text, with its leading and trailing trivia written out
+    fn attached(source: &str) -> Vec<(String, &str, String)> {
+        let cst     = cst(source);
+        let written = |trivia: &[Token]| trivia.iter().map(|t| t.content()).collect::<String>();
+
+        cst.tokens.iter().zip(cst.trivia.iter())
+            .filter(|&(token, _)| !is_layout(token) || token.token_type == TokenType::EOF)
+            .map(|(token, trivia)| (written(&trivia.leading), token.content(), written(&trivia.trailing)))
+            .collect()
+    }
+
+    fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+        Span::new(TokenPosition::new(start.0, start.1), TokenPosition::new(end.0, end.1))
+    }
+
+    #[test]
+    fn round_trip() {
+        let sources = [
+            "",
+            "a = 1",
+            "\n\n  \r\n",
+            "~ a comment\n\n\na = 1 ~ trailing\n\n",
+            "~~ a block\n   comment ~~ a = 1 ~~ and\nanother ~~\n",
+            "a = \"~ not a comment\" ~~ inline ~~ ++ 1   \n",
+            "if a\n\tprint a\n\n\tprint b ~ b\n~ dedented\nb = 2\n",
+            "total = 1 + \\\n  2 \\ \n  + 3\nb = (1,\n      2)\n",
+            "if a\n  if b\n    c",
+        ];
+
+        for source in sources.iter() {
+            assert_eq!(cst(source).to_string(), *source);
+        }
+    }
+
+    #[test]
+    fn samples_round_trip() {
+        for entry in fs::read_dir("samples").unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.extension().is_some_and(|e| e == "plof") {
+                let source = fs::read_to_string(&path).unwrap();
+                assert!(cst(&source).to_string() == source, "{}", path.display());
+            }
+        }
+    }
+
+    #[test]
+    fn trivia() {
+        let source = "~ leads a\na = 1 ~ trails 1\n\n~ leads b\nb ~~ trails b ~~\n~ left for the end";
+
+        assert_eq!(attached(source), [
+            ("~ leads a\n".to_owned(), "a", " ".to_owned()),
+            (String::new(), "=", " ".to_owned()),
+            (String::new(), "1", " ~ trails 1".to_owned()),
+            ("\n\n~ leads b\n".to_owned(), "b", " ~~ trails b ~~".to_owned()),
+            ("\n~ left for the end".to_owned(), "", String::new()),
+        ]);
+    }
+
+    #[test]
+    fn trivia_in_blocks() {
+        // an indent's whitespace goes to the first token on the line, a comment's line to the next
+        let source = "if a\n  ~ about b\n  b\nc\n";
+
+        assert_eq!(attached(source), [
+            (String::new(), "if", " ".to_owned()),
+            (String::new(), "a", String::new()),
+            ("\n  ~ about b\n  ".to_owned(), "b", String::new()),
+            ("\n".to_owned(), "c", String::new()),
+            ("\n".to_owned(), "", String::new()),
+        ]);
+    }
+
+    #[test]
+    fn spans() {
+        let tree = cst("a = 1\nif a\n  b = 2\n\n  c\nd\n");
+        let root = &tree.root;
+
+        let kinds: Vec<_> = root.children.iter().map(|n| n.kind).collect();
+        assert_eq!(kinds, [NodeKind::Line, NodeKind::Line, NodeKind::Block, NodeKind::Line]);
+
+        assert_eq!(root.children[0].span, span((1, 0), (1, 5)));
+        assert_eq!(root.children[1].span, span((2, 0), (2, 4)));
+        assert_eq!(root.children[3].span, span((6, 0), (6, 1)));
+
+        let block = &root.children[2];
+        assert_eq!(block.span, span((3, 0), (6, 0)));
+
+        let lines: Vec<_> = block.children.iter().map(|n| (n.kind, n.span)).collect();
+        assert_eq!(lines, [(NodeKind::Line, span((3, 2), (3, 7))), (NodeKind::Line, span((5, 2), (5, 3)))]);
+
+        assert_eq!(root.span, span((1, 0), (6, 1)));
+        assert_eq!(root.tokens, 0 .. tree.tokens.len());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use super::lexer::{Token, TokenType, Span};
+
+// error codes, by the phase reporting them
+
+pub const E_INTERNAL:         &str = "E0000";
+
+pub const E_LEXICAL:          &str = "E0100";
+
+pub const E_UNEXPECTED:       &str = "E0200";
+pub const E_EXPECTED:         &str = "E0201";
+pub const E_EXPECTED_BLOCK:   &str = "E0202";
+pub const E_LOOP_NAMES:       &str = "E0203";
+pub const E_NUMBER:           &str = "E0204";
+pub const E_INTERPOLATION:    &str = "E0205";
+
+pub const E_UNDECLARED:       &str = "E0300";
+pub const E_MISMATCH:         &str = "E0301";
+pub const E_CHANGED_TYPE:     &str = "E0302";
+pub const E_REDEFINED:        &str = "E0303";
+pub const E_RETURN_TYPE:      &str = "E0304";
+pub const E_BAD_ARGS:         &str = "E0305";
+pub const E_NOT_CALLABLE:     &str = "E0306";
+pub const E_CONDITION:        &str = "E0307";
+pub const E_NOT_ITERABLE:     &str = "E0308";
+pub const E_RANGE:            &str = "E0309";
+pub const E_OPERATION:        &str = "E0310";
+
+// warnings, by the same phases
+
+pub const W_UNUSED:           &str = "W0300";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Error   => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Note    => write!(f, "note"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span:    Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(span: Span, message: &str) -> Label {
+        Label {
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity:    Severity,
+    pub code:        &'static str,
+    pub message:     String,
+    pub primary:     Option<Label>,
+    pub secondary:   Vec<Label>,
+    pub notes:       Vec<String>,
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: &str) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code,
+            message:     message.to_owned(),
+            primary:     None,
+            secondary:   Vec::new(),
+            notes:       Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+
+    pub fn error(code: &'static str, message: &str) -> Diagnostic {
+        Diagnostic::new(Severity::Error, code, message)
+    }
+
+    pub fn warning(code: &'static str, message: &str) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, code, message)
+    }
+
+    pub fn with_span(self, span: Span) -> Diagnostic {
+        self.with_label(span, "")
+    }
+
+    pub fn with_label(mut self, span: Span, message: &str) -> Diagnostic {
+        self.primary = Some(Label::new(span, message));
+        self
+    }
+
+    pub fn with_secondary(mut self, span: Span, message: &str) -> Diagnostic {
+        self.secondary.push(Label::new(span, message));
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Diagnostic {
+        self.notes.push(note.to_owned());
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: &str) -> Diagnostic {
+        self.suggestions.push(suggestion.to_owned());
+        self
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        self.primary.as_ref().map(|l| l.span)
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+// lets a single failure be `?`-ed out of something reporting many
+impl From<Diagnostic> for Vec<Diagnostic> {
+    fn from(d: Diagnostic) -> Vec<Diagnostic> {
+        vec![d]
+    }
+}
+
+// the lexer leaves its errors in the token stream; these are them, in source order
+pub fn lexical_errors<'t, 'a: 't, I: IntoIterator<Item = &'t Token<'a>>>(tokens: I) -> Vec<Diagnostic> {
+    let mut errors: Vec<Diagnostic> = tokens.into_iter().filter_map(|token| match token.token_type {
+        TokenType::Error(ref why) => Some(Diagnostic::error(E_LEXICAL, why).with_span(token.span)),
+        _                         => None,
+    }).collect();
+
+    // blocks are laid out after the code around them
+    errors.sort_by_key(|e| e.span().map(|s| (s.start.line, s.start.col)));
+    errors
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}]", self.severity, self.code)?;
+
+        if let Some(span) = self.span() {
+            write!(f, " {}", span)?;
+        }
+
+        write!(f, ": {}", self.message)?;
+
+        if let Some(ref label) = self.primary {
+            if !label.message.is_empty() {
+                write!(f, "\n  {}: {}", label.span, label.message)?;
+            }
+        }
+
+        for label in self.secondary.iter() {
+            write!(f, "\n  {}: {}", label.span, label.message)?;
+        }
+
+        for note in self.notes.iter() {
+            write!(f, "\n  = note: {}", note)?;
+        }
+
+        for suggestion in self.suggestions.iter() {
+            write!(f, "\n  = help: {}", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
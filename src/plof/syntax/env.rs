@@ -2,7 +2,8 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::fmt;
 
-use super::{RunResult, RunError};
+use super::RunResult;
+use super::diagnostic::{Diagnostic, E_INTERNAL};
 
 use super::parser::Type;
 
@@ -12,10 +13,10 @@ pub struct Env {
 }
 
 impl Env {
-    pub fn new(parent: Rc<Env>, types: &Vec<Type>) -> Env {
+    pub fn new(parent: Rc<Env>, types: &[Type]) -> Env {
         Env {
             parent: Some(parent),
-            types: RefCell::new(types.clone()),
+            types: RefCell::new(types.to_vec()),
         }
     }
 
@@ -46,12 +47,12 @@ impl Env {
                     *v = t;
                     Ok(())
                 },
-                None => Err(RunError::new(&format!("can't set type of invalid type index: {}", index))),
+                None => Err(Diagnostic::error(E_INTERNAL, &format!("can't set type of invalid type index: {}", index))),
             }
         } else {
             match self.parent {
                 Some(ref p) => p.set_type(index, env_index - 1, t),
-                None => Err(RunError::new(&format!("can't set type with invalid env index: {}", env_index))),
+                None => Err(Diagnostic::error(E_INTERNAL, &format!("can't set type with invalid env index: {}", env_index))),
             }
         }
     }
@@ -60,12 +61,12 @@ impl Env {
         if env_index == 0 {
             match self.types.borrow().get(index) {
                 Some(v) => Ok(v.clone()),
-                None    => Err(RunError::new(&format!("can't get type of invalid type index: {}", index))),
+                None    => Err(Diagnostic::error(E_INTERNAL, &format!("can't get type of invalid type index: {}", index))),
             }
         } else {
             match self.parent {
                 Some(ref p) => p.get_type(index, env_index - 1),
-                None => Err(RunError::new(&format!("can't get type with invalid env index: {}", index))),
+                None => Err(Diagnostic::error(E_INTERNAL, &format!("can't get type with invalid env index: {}", index))),
             }
         }
     }
@@ -86,13 +87,13 @@ impl Env {
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
-                try!(p.dump(f, env_index - 1));
-                try!(writeln!(f, "------------------------------"));
+                p.dump(f, env_index - 1)?;
+                writeln!(f, "------------------------------")?;
             }
         }
 
         for (i, v) in self.types.borrow().iter().enumerate() {
-            try!(writeln!(f, "({} : {}) = {:?}", i, env_index, v))
+            writeln!(f, "({} : {}) = {:?}", i, env_index, v)?
         }
 
         Ok(())
@@ -109,7 +110,7 @@ impl Env {
 
 impl fmt::Debug for Env {
     fn fmt(&self, f : &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        try!(self.dump(f, 0));
+        self.dump(f, 0)?;
         Ok(())
     }
 }
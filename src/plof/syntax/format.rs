@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+
+use super::cst::{Cst, Node, NodeKind};
+use super::lexer::{Token, TokenType};
+
+// the indentation of every block, and of every line carried on from the one before it
+pub const INDENT_WIDTH: usize = 2;
+
+// prints a `Cst` out the one way plof is written: blocks indented by `INDENT_WIDTH`,
+// single spaces between tokens except where brackets, ',', ':' and prefix operators hug
+// them, tables with keys one entry per line, comments where they were, and no more than
+// a single blank line in a row; only whitespace ever changes, and none of it that
+// changes what the code means, like the space before the '!' in 'f !x'
+pub struct Formatter<'c, 'a: 'c> {
+    cst:   &'c Cst<'a>,
+    out:   String,
+    skip:  Vec<usize>, // how much of every token's leading trivia is printed already
+    fresh: bool,       // whether nothing's been printed in the block yet
+}
+
+fn is(token: &Token, symbol: &str) -> bool {
+    token.token_type == TokenType::Symbol && token.content() == symbol
+}
+
+fn is_opening(token: &Token) -> bool {
+    is(token, "(") || is(token, "[")
+}
+
+fn is_closing(token: &Token) -> bool {
+    is(token, ")") || is(token, "]")
+}
+
+fn breaks(token: &Token) -> bool {
+    token.token_type == TokenType::Whitespace && token.content().contains('\n')
+}
+
+pub fn format(cst: &Cst) -> String {
+    let mut formatter = Formatter {
+        cst,
+        out:   String::new(),
+        skip:  vec![0; cst.tokens.len()],
+        fresh: true,
+    };
+
+    formatter.node(&cst.root, 0);
+
+    let mut out = formatter.out.trim_end_matches('\n').to_owned();
+
+    if !out.is_empty() {
+        out.push('\n')
+    }
+
+    out
+}
+
+impl<'c, 'a> Formatter<'c, 'a> {
+    fn tokens(&self) -> &'c [Token<'a>] {
+        &self.cst.tokens
+    }
+
+    fn indent(&mut self, level: usize) {
+        for _ in 0 .. level * INDENT_WIDTH {
+            self.out.push(' ')
+        }
+    }
+
+    // a blank line, for one or more of them in the source
+    fn gap(&mut self, newlines: usize) {
+        if newlines > 1 && !self.fresh {
+            self.out.push('\n')
+        }
+    }
+
+    fn node(&mut self, node: &Node, level: usize) {
+        match node.kind {
+            NodeKind::Line => self.line(node, level),
+
+            NodeKind::File | NodeKind::Block => {
+                let level = if node.kind == NodeKind::Block { level + 1 } else { level };
+
+                self.fresh = true;
+
+                for child in node.children.iter() {
+                    self.node(child, level)
+                }
+
+                // comments after the last line, indented like the block or further, stay in it
+                let first = node.tokens.start + 1;
+                let next  = (node.tokens.end .. self.tokens().len()).find(|&i| self.tokens()[i].token_type != TokenType::Dedent);
+
+                match (node.kind, next) {
+                    (NodeKind::Block, Some(next)) if first < self.tokens().len() => {
+                        let col = self.tokens()[first].span.start.col;
+                        self.comments(next, level, col)
+                    },
+
+                    (NodeKind::File, _) if !self.tokens().is_empty() => {
+                        let end = self.tokens().len() - 1;
+                        self.comments(end, level, 0)
+                    },
+
+                    _ => (),
+                }
+            },
+        }
+    }
+
+    // the comments leading token `i` on lines of their own, as far as they're indented
+    // to at least `col`
+    fn comments(&mut self, i: usize, level: usize, col: usize) {
+        let leading = &self.cst.trivia[i].leading;
+
+        let mut newlines = 0;
+
+        for (k, trivia) in leading.iter().enumerate().skip(self.skip[i]) {
+            if breaks(trivia) {
+                newlines += trivia.content().matches('\n').count();
+                continue
+            }
+
+            if trivia.token_type != TokenType::Comment {
+                continue
+            }
+
+            if trivia.span.start.col < col {
+                break
+            }
+
+            self.gap(newlines);
+            self.indent(level);
+            self.out.push_str(trivia.content());
+            self.out.push('\n');
+
+            self.fresh   = false;
+            self.skip[i] = k + 1;
+            newlines     = 0
+        }
+    }
+
+    // the blank lines between the comments leading token `i` and the token itself
+    fn newlines(&self, i: usize) -> usize {
+        self.cst.trivia[i].leading.iter().skip(self.skip[i]).filter(|t| breaks(t)).map(|t| t.content().matches('\n').count()).sum()
+    }
+
+    fn line(&mut self, node: &Node, level: usize) {
+        let start = node.tokens.start;
+        let end   = match self.tokens()[node.tokens.end - 1].token_type {
+            TokenType::EOL => node.tokens.end - 1,
+            _              => node.tokens.end,
+        };
+
+        let forced = self.tables(start, end);
+
+        self.comments(start, level, 0);
+
+        let newlines = self.newlines(start);
+        self.gap(newlines);
+        self.indent(level);
+
+        let mut depth = 0;
+
+        for i in start .. end {
+            let token = &self.tokens()[i];
+
+            if i > start {
+                let broken = self.cst.trivia[i].leading.iter().skip(self.skip[i]).any(|t| breaks(t) || t.content().contains('\n'))
+                             || self.cst.trivia[i - 1].trailing.iter().any(|t| t.content().contains('\n'));
+
+                if broken || forced.contains(&i) {
+                    let previous = &self.tokens()[i - 1];
+                    let carried  = previous.token_type == TokenType::Operator || is(previous, ",");
+
+                    // outside of brackets, only a trailing '\' carries the line on over plain code
+                    if depth == 0 && !carried {
+                        self.out.push_str(" \\")
+                    }
+
+                    let inner = match depth {
+                        0 => 2,
+                        d if is_closing(token) => d - 1,
+                        d => d,
+                    };
+
+                    self.out.push('\n');
+                    self.comments(i, level + inner, 0);
+                    self.indent(level + inner)
+                } else {
+                    self.inline(i);
+
+                    if self.spaced(i) {
+                        self.out.push(' ')
+                    }
+                }
+            }
+
+            self.out.push_str(token.content());
+
+            for trivia in self.cst.trivia[i].trailing.iter() {
+                if trivia.token_type == TokenType::Comment {
+                    self.out.push(' ');
+                    self.out.push_str(trivia.content())
+                }
+            }
+
+            if is_opening(token) {
+                depth += 1
+            } else if is_closing(token) && depth > 0 {
+                depth -= 1
+            }
+        }
+
+        self.out.push('\n');
+        self.fresh = false
+    }
+
+    // block comments between two tokens on the same line
+    fn inline(&mut self, i: usize) {
+        for trivia in self.cst.trivia[i].leading.iter().skip(self.skip[i]) {
+            if trivia.token_type == TokenType::Comment {
+                self.out.push(' ');
+                self.out.push_str(trivia.content())
+            }
+        }
+    }
+
+    // where a line breaks in its tables with keys: after the '[', after every ',' between
+    // entries, and before the ']'
+    fn tables(&self, start: usize, end: usize) -> HashSet<usize> {
+        let tokens = self.tokens();
+        let mut forced = HashSet::new();
+
+        for open in start .. end {
+            if !is(&tokens[open], "[") {
+                continue
+            }
+
+            let mut depth   = 0;
+            let mut close   = None;
+            let mut commas  = Vec::new();
+            let mut entry   = open + 1;
+            let mut keyed   = false;
+
+            for i in open .. end {
+                let token = &tokens[i];
+
+                if is_opening(token) {
+                    depth += 1
+                } else if is_closing(token) {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        close = Some(i);
+                        break
+                    }
+                } else if depth == 1 && is(token, ",") {
+                    commas.push(i + 1);
+                    entry = i + 1
+                } else if depth == 1 && is(token, ":") {
+                    let name = i > entry && tokens[i - 1].token_type == TokenType::Identifier;
+                    let typed = i == entry + 2 && tokens[entry].token_type == TokenType::Type;
+
+                    keyed = keyed || (name && (i == entry + 1 || typed))
+                }
+            }
+
+            if let (true, Some(close)) = (keyed, close) {
+                forced.insert(open + 1);
+                forced.extend(commas.into_iter().filter(|&i| i < close));
+                forced.insert(close);
+            }
+        }
+
+        forced
+    }
+
+    // whether token `i` is a value a '-' or '!' after it would be infix to
+    fn is_value(&self, i: usize) -> bool {
+        let token = &self.tokens()[i];
+
+        match token.token_type {
+            TokenType::IntLiteral |
+            TokenType::FloatLiteral |
+            TokenType::StringLiteral |
+            TokenType::InterpolatedLiteral |
+            TokenType::BoolLiteral |
+            TokenType::Identifier => true,
+            TokenType::Symbol     => is_closing(token) || self.bang_call(i),
+            _                     => false,
+        }
+    }
+
+    fn spaced_in_source(&self, i: usize) -> bool {
+        self.tokens()[i - 1].span.end != self.tokens()[i].span.start
+    }
+
+    // the '!' of 'f!', calling without arguments
+    fn bang_call(&self, i: usize) -> bool {
+        is(&self.tokens()[i], "!") && i > 0 && self.tokens()[i - 1].token_type == TokenType::Identifier && !self.spaced_in_source(i)
+    }
+
+    // a '-', '!', '#' or 'not' negating, measuring or calling what's after it
+    fn is_prefix(&self, i: usize) -> bool {
+        let token = &self.tokens()[i];
+
+        match token.token_type {
+            TokenType::Operator if token.content() == "#" => true,
+            TokenType::Symbol if token.content() == "!"   => !self.bang_call(i),
+            TokenType::Operator if token.content() == "-" => {
+                if i == 0 || !self.is_value(i - 1) {
+                    return true
+                }
+
+                // 'f -x' passes '-x' along, see `Parser::prefix_minus`
+                let touching = self.tokens().get(i + 1).is_some_and(|after| after.span.start == token.span.end);
+
+                self.tokens()[i - 1].token_type == TokenType::Identifier && self.spaced_in_source(i) && touching
+            },
+            _ => false,
+        }
+    }
+
+    // whether a space goes between token `i` and the one before it
+    fn spaced(&self, i: usize) -> bool {
+        let tokens = self.tokens();
+
+        let before = &tokens[i - 1];
+        let token  = &tokens[i];
+
+        let comment = |t: &Token| t.token_type == TokenType::Comment;
+
+        // a comment in between keeps its space after
+        if self.cst.trivia[i].leading.iter().skip(self.skip[i]).any(comment) || self.cst.trivia[i - 1].trailing.iter().any(comment) {
+            return true
+        }
+
+        if is_opening(before) || is_closing(token) || is(token, ",") || is(token, ":") {
+            return false
+        }
+
+        if is(token, "..") || is(before, "..") || is(token, ".") || is(before, ".") {
+            return false
+        }
+
+        if is(token, "...") && before.token_type == TokenType::Type {
+            return false
+        }
+
+        // 'f!' and 'f !x' mean different things
+        if is(token, "!") && before.token_type == TokenType::Identifier {
+            return self.spaced_in_source(i)
+        }
+
+        !self.is_prefix(i - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::lexer::Layout;
+    use super::super::parser::{Parser, Traveler};
+
+    use std::fs;
+
+    fn fmt(source: &str) -> String {
+        format(&Cst::new(Layout::new(source).with_trivia()))
+    }
+
+    // the lua a source becomes, brackets around every operation and all
+    fn lua(source: &str) -> String {
+        let tokens: Vec<Token> = Layout::new(source).collect();
+        let (statements, errors) = Parser::new(Traveler::new(&tokens)).parse();
+
+        assert!(errors.is_empty(), "{:?}", source);
+
+        statements.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn idempotent() {
+        let mut sources = vec![
+            "a   =  1+2\n\n\n\nb=[x:1,y:[1,2]]\n".to_owned(),
+            "num(num a,num b)add=\n      a+b ~ sum\n".to_owned(),
+            "if a\n    ~ lone\n    b = 1 + \\\n            2\n".to_owned(),
+        ];
+
+        for entry in fs::read_dir("samples").unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.extension().is_some_and(|e| e == "plof") {
+                sources.push(fs::read_to_string(&path).unwrap())
+            }
+        }
+
+        for source in sources.iter() {
+            let once = fmt(source);
+            assert_eq!(fmt(&once), once, "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn comments() {
+        let source = "~ first\n\n\n\na  =  1   ~ after a\nb = ~~ in b ~~ 2\nif a\n    ~ in the block\n    c = 3\n    ~ ends the block\n~ ends the file\n";
+
+        assert_eq!(fmt(source), "~ first\n\na = 1 ~ after a\nb = ~~ in b ~~ 2\nif a\n  ~ in the block\n  c = 3\n  ~ ends the block\n~ ends the file\n");
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(fmt("a = [ 1 ,2,  3 ]\n"), "a = [1, 2, 3]\n");
+        assert_eq!(fmt("a = [x:1, y : [1,2]]\n"), "a = [\n  x: 1,\n  y: [1, 2]\n]\n");
+        assert_eq!(fmt("a = [ (1 + 2) , f[ 1 ] ]\n"), "a = [(1 + 2), f [1]]\n");
+    }
+
+    #[test]
+    fn signatures() {
+        assert_eq!(fmt("num(num a,num b)add=\n a+b\n"), "num (num a, num b) add =\n  a + b\n");
+        assert_eq!(fmt("any ( num...  rest ) f = true\n"), "any (num... rest) f = true\n");
+    }
+
+    #[test]
+    fn meaning() {
+        let sources = [
+            ("print f !x\n",    "print f !x\n"),
+            ("print f!\n",      "print f!\n"),
+            ("print f -x\n",    "print f -x\n"),
+            ("print f - x\n",   "print f - x\n"),
+            ("print f-x\n",     "print f - x\n"),
+            ("print a  - -b\n", "print a - -b\n"),
+            ("print # t\n",     "print #t\n"),
+        ];
+
+        for &(source, formatted) in sources.iter() {
+            assert_eq!(fmt(source), formatted);
+            assert_eq!(lua(formatted), lua(source), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn indentation() {
+        let source = "if a\n\tif b\n\t\t\tc = 1\n\telse\n        c = 2\nd = [1,\n2]\n";
+
+        assert_eq!(fmt(source), "if a\n  if b\n    c = 1\n  else\n    c = 2\nd = [1,\n  2]\n");
+        assert!(fmt(source).lines().all(|l| (l.len() - l.trim_start().len()) % INDENT_WIDTH == 0));
+    }
+}
@@ -0,0 +1,244 @@
+use std::collections::VecDeque;
+
+use super::token::{Token, TokenType, TokenPosition, Span};
+use super::scanner::Scanner;
+
+pub const TAB_WIDTH: usize = 4;
+
+// the offside rule over the scanner's tokens: an EOL ends each logical line, Indents and
+// Dedents open and close blocks, and an EOF ends the source
+pub struct Layout<'a> {
+    source:    &'a str,
+    scanner:   Scanner<'a>,
+    tab_width: usize,
+    trivia:    bool,
+    levels:    Vec<usize>, // the indentation of every open block, outermost first
+    brackets:  usize,
+    // the leading whitespace of the line the next logical line starts on, with where that line starts
+    leading:   (&'a str, TokenPosition),
+    on_line:   bool, // whether the current logical line has code on it yet
+    indent:    usize, // the indentation of the line the current logical line started on
+    trailing:  bool, // whether the last code wants more after it
+    // where the line ended, when it's carried on as long as the next one is indented further
+    carried:   Option<TokenPosition>,
+    backslash: Option<Span>, // the '\' carrying it on, if that's what did
+    started:   bool,
+    end:       TokenPosition, // the end of the last token that wasn't whitespace
+    pending:   VecDeque<Token<'a>>,
+    done:      bool,
+}
+
+impl<'a> Layout<'a> {
+    pub fn new(source: &'a str) -> Layout<'a> {
+        let start = TokenPosition::default();
+
+        Layout {
+            source,
+            scanner:   Scanner::new(source, start),
+            tab_width: TAB_WIDTH,
+            trivia:    false,
+            levels:    vec![0],
+            brackets:  0,
+            leading:   ("", start),
+            on_line:   false,
+            indent:    0,
+            trailing:  false,
+            carried:   None,
+            backslash: None,
+            started:   false,
+            end:       start,
+            pending:   VecDeque::new(),
+            done:      false,
+        }
+    }
+
+    // a tab indents to the next multiple of `width` columns
+    pub fn with_tab_width(mut self, width: usize) -> Layout<'a> {
+        self.tab_width = width.max(1);
+        self
+    }
+
+    // keeps whitespace and comments in, for a `Cst`
+    pub fn with_trivia(mut self) -> Layout<'a> {
+        self.trivia = true;
+        self
+    }
+
+    // in columns, a tab going on to the next multiple of the tab width
+    fn width(&self, leading: &str) -> usize {
+        leading.chars().fold(0, |pos, c| match c {
+            '\t' => (pos / self.tab_width + 1) * self.tab_width,
+            _    => pos + 1,
+        })
+    }
+
+    fn push(&mut self, token_type: TokenType, span: Span, content: &'a str) {
+        self.pending.push_back(Token::new(token_type, span, content))
+    }
+
+    fn error(&mut self, span: Span, message: &str) {
+        self.push(TokenType::Error(message.to_owned()), span, "")
+    }
+
+    // opens or closes blocks before the first code of a logical line; a dedent has to land
+    // on a level that's open, and one indentation can't mix tabs and spaces
+    fn lay_out(&mut self) {
+        let (leading, start) = self.leading;
+
+        let width = self.width(leading);
+        let at    = TokenPosition::new(start.line, leading.chars().count());
+        let span  = Span::new(start, at);
+
+        if leading.contains(' ') && leading.contains('\t') {
+            self.error(span, "mixed tabs and spaces in indentation")
+        }
+
+        let current = *self.levels.last().unwrap();
+
+        self.indent = width;
+
+        if width > current {
+            if self.started {
+                self.levels.push(width);
+                self.push(TokenType::Indent, span, leading)
+            } else {
+                self.error(span, "unexpected indent")
+            }
+        } else if width < current {
+            while *self.levels.last().unwrap() > width {
+                self.levels.pop();
+                self.push(TokenType::Dedent, Span::new(at, at), "")
+            }
+
+            if *self.levels.last().unwrap() < width {
+                self.error(span, "unindent doesn't match any outer indentation level")
+            }
+        }
+
+        self.started = true
+    }
+
+    fn finish(&mut self) {
+        let end = self.end;
+
+        if self.on_line {
+            self.push(TokenType::EOL, Span::new(end, end), "\n")
+        }
+
+        while self.levels.len() > 1 {
+            self.levels.pop();
+            self.push(TokenType::Dedent, Span::new(end, end), "")
+        }
+
+        self.push(TokenType::EOF, Span::new(end, end), "");
+        self.done = true
+    }
+
+    fn advance(&mut self) {
+        let from  = self.scanner.offset();
+        let token = match Scanner::scan(&mut self.scanner) {
+            Some(t) => t,
+            None    => return self.finish(),
+        };
+
+        match token.token_type {
+            TokenType::Whitespace => {
+                let content = &self.source[from .. self.scanner.offset()];
+
+                // only a line break that nothing carries on over ends a line: open brackets
+                // carry it on however the next line is indented, a trailing operator, ',' or
+                // '\' only onto one indented further
+                if let Some(i) = content.rfind('\n') {
+                    if self.on_line && self.brackets == 0 && self.carried.is_none() {
+                        let end = self.end;
+
+                        if let Some(b) = content.find('\\') {
+                            let at = TokenPosition::new(token.span.start.line, token.span.start.col + content[.. b].chars().count());
+
+                            self.backslash = Some(Span::new(at, TokenPosition::new(at.line, at.col + 1)));
+                            self.carried   = Some(end)
+                        } else if self.trailing {
+                            self.carried = Some(end)
+                        } else {
+                            self.push(TokenType::EOL, Span::new(end, end), "\n");
+                            self.on_line = false
+                        }
+                    }
+
+                    self.leading = (&content[i + 1 ..], TokenPosition::new(token.span.end.line, 0))
+                } else if token.span.start.col == 0 {
+                    self.leading = (content, token.span.start)
+                }
+
+                if self.trivia {
+                    self.pending.push_back(token)
+                }
+            },
+
+            // code after a block comment is indented like the line the comment opened on
+            TokenType::Comment => {
+                self.end = token.span.end;
+
+                if self.trivia {
+                    self.pending.push_back(token)
+                }
+            },
+
+            _ => {
+                if let Some(end) = self.carried.take() {
+                    let backslash = self.backslash.take();
+
+                    // not indented any further, so it's a line of its own after all
+                    if self.width(self.leading.0) <= self.indent {
+                        if let Some(span) = backslash {
+                            self.error(span, "expected an indented line after '\\'")
+                        }
+
+                        self.push(TokenType::EOL, Span::new(end, end), "\n");
+                        self.on_line = false
+                    }
+                }
+
+                if !self.on_line {
+                    self.lay_out();
+                    self.on_line = true
+                }
+
+                if token.token_type == TokenType::Symbol {
+                    match token.content() {
+                        "(" | "[" | "{" => self.brackets += 1,
+                        ")" | "]" | "}" => self.brackets = self.brackets.saturating_sub(1),
+                        _               => (),
+                    }
+                }
+
+                self.trailing = match token.token_type {
+                    TokenType::Operator => true,
+                    TokenType::Symbol   => token.content() == ",",
+                    _                   => false,
+                };
+
+                self.end = token.span.end;
+                self.pending.push_back(token)
+            },
+        }
+    }
+}
+
+impl<'a> Iterator for Layout<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            if let Some(token) = self.pending.pop_front() {
+                return Some(token)
+            }
+
+            if self.done {
+                return None
+            }
+
+            self.advance()
+        }
+    }
+}
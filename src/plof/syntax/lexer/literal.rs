@@ -0,0 +1,178 @@
+use std::mem;
+
+use super::token::{TokenPosition, Span};
+
+// the value of a number literal as written, or why it's malformed
+pub fn parse_number(literal: &str) -> Result<f64, String> {
+    let digits: String = literal.chars().filter(|&c| c != '_').collect();
+
+    let (radix, body) = match digits.get(.. 2) {
+        Some("0x") | Some("0X") => (16, &digits[2 ..]),
+        Some("0b") | Some("0B") => (2, &digits[2 ..]),
+        Some("0o") | Some("0O") => (8, &digits[2 ..]),
+        _ => (10, digits.as_str()),
+    };
+
+    if radix != 10 {
+        if body.is_empty() {
+            return Err(format!("expected digits after '{}'", &digits[.. 2]))
+        }
+
+        if let Some(c) = body.chars().find(|c| !c.is_digit(radix)) {
+            return Err(format!("'{}' isn't a base {} digit", c, radix))
+        }
+
+        // too many digits for an int are still a fine float, as in lua
+        return Ok(body.chars().fold(0.0, |n, c| n * radix as f64 + c.to_digit(radix).unwrap() as f64))
+    }
+
+    if let Some(c) = body.chars().find(|c| !(c.is_ascii_digit() || "eE+-.".contains(*c))) {
+        return Err(format!("unexpected '{}' in number", c))
+    }
+
+    match body.parse::<f64>() {
+        Ok(n)  => Ok(n),
+        Err(_) => Err("expected digits after the exponent".to_owned()),
+    }
+}
+
+// the character a '\\' escape stands for
+pub fn unescape(c: char) -> Option<char> {
+    match c {
+        '\\' | '\'' | '"' | '{' | '}' => Some(c),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        _   => None,
+    }
+}
+
+// the quotes around a literal as written, and whether it's raw
+fn quoting(written: &str) -> (usize, bool) {
+    let raw  = written.starts_with('r');
+    let body = if raw { &written[1 ..] } else { written };
+
+    let quote  = body.chars().next().unwrap_or('"');
+    let quotes = if body.len() >= 6 && body.starts_with(&quote.to_string().repeat(3)) { 3 } else { 1 };
+
+    (quotes, raw)
+}
+
+// the text of a string literal as written, quotes taken off and escapes worked out
+pub fn string_value(written: &str) -> String {
+    let (quotes, raw) = quoting(written);
+
+    let prefix = if raw { 1 } else { 0 };
+    let body   = &written[prefix + quotes .. written.len() - quotes];
+
+    if raw {
+        return body.to_owned()
+    }
+
+    let mut value = String::new();
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => if let Some(e) = chars.next() {
+                value.push(unescape(e).unwrap_or(e))
+            },
+            c => value.push(c),
+        }
+    }
+
+    value
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringPart {
+    Text(String, Span),
+    Code(String, Span), // inside the braces
+}
+
+// splits an interpolated literal, as written from its opening quotes at `start`, into
+// text and code; on failure, gives why along with where
+pub fn split_interpolated(written: &str, start: TokenPosition) -> Result<Vec<StringPart>, (String, Span)> {
+    let (quotes, _) = quoting(written);
+    let body        = &written[quotes .. written.len() - quotes];
+
+    let mut parts = Vec::new();
+    let mut chars = body.chars().peekable();
+    let mut pos   = TokenPosition::new(start.line, start.col + quotes);
+
+    let mut text       = String::new();
+    let mut text_start = pos;
+
+    fn step(pos: &mut TokenPosition, c: char) {
+        if c == '\n' {
+            pos.line += 1;
+            pos.col   = 0
+        } else {
+            pos.col += 1
+        }
+    }
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let e = chars.next().unwrap_or('\\');
+
+                text.push(unescape(e).unwrap_or(e));
+
+                step(&mut pos, c);
+                step(&mut pos, e)
+            },
+            '{' => {
+                let open = pos;
+                step(&mut pos, c);
+
+                let code_start = pos;
+                let mut code   = String::new();
+                let mut depth  = 1;
+
+                for c in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _   => (),
+                    }
+
+                    if depth == 0 {
+                        break
+                    }
+
+                    code.push(c);
+                    step(&mut pos, c)
+                }
+
+                if depth > 0 {
+                    return Err(("unclosed '{' in string".to_owned(), Span::new(open, TokenPosition::new(open.line, open.col + 1))))
+                }
+
+                let code_end = pos;
+                step(&mut pos, '}');
+
+                if code.trim().is_empty() {
+                    return Err(("nothing to interpolate".to_owned(), Span::new(open, pos)))
+                }
+
+                if !text.is_empty() {
+                    parts.push(StringPart::Text(mem::take(&mut text), Span::new(text_start, open)))
+                }
+
+                parts.push(StringPart::Code(code, Span::new(code_start, code_end)));
+                text_start = pos
+            },
+            c => {
+                text.push(c);
+                step(&mut pos, c)
+            },
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push(StringPart::Text(text, Span::new(text_start, pos)))
+    }
+
+    Ok(parts)
+}
@@ -1,11 +1,9 @@
 pub mod token;
-pub mod block_tree;
-pub mod tokenizer;
-pub mod matcher;
-pub mod lexer;
+pub mod literal;
+pub mod scanner;
+pub mod layout;
 
 pub use self::token::*;
-pub use self::block_tree::*;
-pub use self::tokenizer::*;
-pub use self::matcher::*;
-pub use self::lexer::*;
+pub use self::literal::*;
+pub use self::scanner::*;
+pub use self::layout::*;
@@ -0,0 +1,431 @@
+use super::token::{Token, TokenType, TokenPosition, Span};
+use super::literal::unescape;
+
+pub const SYMBOLS: &[&str] = &[
+    "(",
+    ")",
+    "[",
+    "]",
+    ",",
+    ":",
+    "{",
+    "}",
+    "!",
+    "|",
+    "=",
+    "...",
+    "..",
+    ".",
+];
+
+// matched ahead of symbols, so '==' doesn't end up as '=' '='
+pub const COMPARISONS: &[&str] = &[
+    "==",
+    "!=",
+    ">=",
+    "<=",
+];
+
+pub const OPERATORS: &[&str] = &[
+    "++",
+    "+",
+    "-",
+    "*",
+    "/",
+    "%",
+    "^",
+    "#",
+    ">",
+    "<",
+];
+
+// whole words only, so 'android' stays an identifier
+pub const WORD_OPERATORS: &[&str] = &[
+    "and",
+    "or",
+    "not",
+];
+
+pub const KEYWORDS: &[&str] = &[
+    "if", "else", "elif", "unless", "return", "while", "for", "in",
+];
+
+pub const TYPES: &[&str] = &[
+    "num", "str", "any", "bool",
+];
+
+pub const BOOLEANS: &[&str] = &[
+    "true",
+    "false",
+];
+
+// a single pass over the source, by byte offset, without copying the input or backtracking
+// over it; benches/lexer.rs checks it against the matcher pipeline it replaced
+pub struct Scanner<'a> {
+    source: &'a str,
+    offset: usize,
+    pos:    TokenPosition,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || "_@?'".contains(c)
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(source: &'a str, start: TokenPosition) -> Scanner<'a> {
+        Scanner {
+            source,
+            offset: 0,
+            pos:    start,
+        }
+    }
+
+    // how far into the source it's got, in bytes
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.offset ..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn peek_n(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+
+        self.offset += c.len_utf8();
+
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.col   = 0
+        } else {
+            self.pos.col += 1
+        }
+
+        Some(c)
+    }
+
+    // skips `text`, which must be what's ahead
+    fn skip(&mut self, text: &str) {
+        for _ in text.chars() {
+            self.bump();
+        }
+    }
+
+    fn bump_while<F: Fn(char) -> bool>(&mut self, f: F) {
+        while self.peek().is_some_and(&f) {
+            self.bump();
+        }
+    }
+
+    // the longest of `constants` ahead, if any
+    fn constant(&self, constants: &[&'static str]) -> Option<&'static str> {
+        let rest = self.rest();
+
+        constants.iter().cloned().filter(|c| rest.starts_with(c)).max_by_key(|c| c.len())
+    }
+
+    // a token of everything from byte `from` up to here
+    fn token(&self, token_type: TokenType, start: TokenPosition, from: usize) -> Option<Token<'a>> {
+        Some(Token::new(token_type, Span::new(start, self.pos), &self.source[from .. self.offset]))
+    }
+
+    // a '\' with nothing but the end of the line after it
+    fn continuation(&self) -> bool {
+        let rest = self.rest();
+
+        rest.starts_with('\\') && rest[1 ..].trim_start_matches([' ', '\t', '\r']).starts_with('\n')
+    }
+
+    fn comment(&mut self) -> TokenType {
+        if self.rest().starts_with("~~") {
+            self.skip("~~");
+
+            match self.rest().find("~~") {
+                Some(end) => {
+                    let body = &self.rest()[.. end + 2];
+                    self.skip(body)
+                },
+                None => {
+                    self.bump_while(|_| true);
+                    return TokenType::Error("unterminated block comment".to_owned())
+                },
+            }
+        } else {
+            self.bump_while(|c| c != '\n')
+        }
+
+        TokenType::Comment
+    }
+
+    // whether it's a float
+    fn number(&mut self) -> bool {
+        let mut float = false;
+
+        let radix = self.peek() == Some('0') && self.peek_n(1).is_some_and(|c| "xXbBoO".contains(c));
+
+        if radix {
+            self.bump();
+            self.bump();
+        } else {
+            while let Some(c) = self.peek() {
+                if c.is_ascii_digit() || c == '_' {
+                    self.bump();
+                } else if c == '.' && !float && self.peek_n(1).is_some_and(|n| n.is_ascii_digit()) {
+                    float = true;
+                    self.bump();
+                } else if c == 'e' || c == 'E' {
+                    float = true;
+                    self.bump();
+
+                    if let Some('-') | Some('+') = self.peek() {
+                        self.bump();
+                    }
+                } else {
+                    break
+                }
+            }
+        }
+
+        self.bump_while(|c| c.is_alphanumeric() || c == '_');
+
+        float
+    }
+
+    // a quoted literal, '"""' ones running over lines; '{' starts an interpolation, which
+    // runs to its matching '}' without escapes
+    fn string(&mut self, raw: bool) -> TokenType {
+        if raw {
+            self.bump();
+        }
+
+        let delimeter = match self.peek() {
+            Some(c) => c,
+            None    => return TokenType::Error("unterminated string".to_owned()),
+        };
+
+        let quotes  = if self.rest().chars().take(3).filter(|&c| c == delimeter).count() == 3 { 3 } else { 1 };
+        let closing = &self.source[self.offset .. self.offset + quotes * delimeter.len_utf8()];
+
+        self.skip(closing);
+
+        let mut bad_escape   = None;
+        let mut terminated   = false;
+        let mut depth        = 0;
+        let mut interpolated = false;
+
+        while let Some(c) = self.peek() {
+            if self.rest().starts_with(closing) {
+                terminated = true;
+                break
+            }
+
+            if c == '\n' && quotes == 1 {
+                break
+            }
+
+            self.bump();
+
+            if raw {
+                continue
+            }
+
+            if depth > 0 {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _   => (),
+                }
+            } else {
+                match c {
+                    '\\' => match self.bump() {
+                        Some(e) if unescape(e).is_none() => bad_escape = bad_escape.or(Some(e)),
+                        Some(_) => (),
+                        None    => break,
+                    },
+                    '{' => {
+                        interpolated = true;
+                        depth = 1
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        if !terminated {
+            return TokenType::Error("unterminated string".to_owned())
+        }
+
+        self.skip(closing);
+
+        if let Some(c) = bad_escape {
+            return TokenType::Error(format!("unknown character escape '\\{}'", c))
+        }
+
+        if interpolated {
+            TokenType::InterpolatedLiteral
+        } else {
+            TokenType::StringLiteral
+        }
+    }
+
+    // the next token, whitespace included
+    pub fn scan(&mut self) -> Option<Token<'a>> {
+        let start = self.pos;
+        let from  = self.offset;
+        let c     = self.peek()?;
+
+        // an explicit line continuation is whitespace too, see `Layout`
+        if c.is_whitespace() || self.continuation() {
+            loop {
+                self.bump_while(char::is_whitespace);
+
+                if !self.continuation() {
+                    break
+                }
+
+                self.bump();
+            }
+
+            return self.token(TokenType::Whitespace, start, from)
+        }
+
+        if c == '~' {
+            let token_type = self.comment();
+            return self.token(token_type, start, from)
+        }
+
+        match (c, self.peek_n(1)) {
+            ('"', _) | ('\'', _) => {
+                let token_type = self.string(false);
+                return self.token(token_type, start, from)
+            },
+            ('r', Some('"')) | ('r', Some('\'')) => {
+                let token_type = self.string(true);
+                return self.token(token_type, start, from)
+            },
+            _ => (),
+        }
+
+        if c.is_ascii_digit() {
+            let token_type = if self.number() { TokenType::FloatLiteral } else { TokenType::IntLiteral };
+            return self.token(token_type, start, from)
+        }
+
+        // a whole word, which is only a keyword and such if it's one as a whole
+        if is_word(c) {
+            self.bump_while(is_word);
+
+            let word = &self.source[from .. self.offset];
+
+            let token_type = if TYPES.contains(&word) {
+                TokenType::Type
+            } else if WORD_OPERATORS.contains(&word) {
+                TokenType::Operator
+            } else if BOOLEANS.contains(&word) {
+                TokenType::BoolLiteral
+            } else if KEYWORDS.contains(&word) {
+                TokenType::Keyword
+            } else {
+                TokenType::Identifier
+            };
+
+            return self.token(token_type, start, from)
+        }
+
+        let symbolic = [
+            (COMPARISONS, TokenType::Operator),
+            (SYMBOLS,     TokenType::Symbol),
+            (OPERATORS,   TokenType::Operator),
+        ];
+
+        for &(constants, ref token_type) in symbolic.iter() {
+            if let Some(constant) = self.constant(constants) {
+                self.skip(constant);
+                return self.token(token_type.clone(), start, from)
+            }
+        }
+
+        self.bump();
+        self.token(TokenType::Error(format!("unexpected character '{}'", c)), start, from)
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        loop {
+            match self.scan() {
+                Some(Token { token_type: TokenType::Whitespace, .. }) => continue,
+                token => return token,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(source: &str) -> Vec<(TokenType, String)> {
+        Scanner::new(source, TokenPosition::default()).map(|t| (t.token_type.clone(), t.content().to_owned())).collect()
+    }
+
+    fn token(token_type: TokenType, content: &str) -> (TokenType, String) {
+        (token_type, content.to_owned())
+    }
+
+    #[test]
+    fn every_operator() {
+        let constants = [
+            (SYMBOLS,     TokenType::Symbol),
+            (COMPARISONS, TokenType::Operator),
+            (OPERATORS,   TokenType::Operator),
+        ];
+
+        for &(constants, ref token_type) in constants.iter() {
+            for c in constants.iter() {
+                // next to words, and last in the source with nothing after it
+                assert_eq!(scan(&format!("a{}b", c)), vec![
+                    token(TokenType::Identifier, "a"), token(token_type.clone(), c), token(TokenType::Identifier, "b"),
+                ], "{}", c);
+
+                assert_eq!(scan(&format!("a {}", c)), vec![
+                    token(TokenType::Identifier, "a"), token(token_type.clone(), c),
+                ], "{}", c);
+            }
+        }
+
+        for c in WORD_OPERATORS.iter() {
+            assert_eq!(scan(&format!("a {} b", c)), vec![
+                token(TokenType::Identifier, "a"), token(TokenType::Operator, c), token(TokenType::Identifier, "b"),
+            ], "{}", c);
+        }
+    }
+
+    #[test]
+    fn longest_match() {
+        let contents = |source| scan(source).into_iter().map(|(_, c)| c).collect::<Vec<_>>();
+
+        assert_eq!(contents("a>=b==c!=d<=e"), ["a", ">=", "b", "==", "c", "!=", "d", "<=", "e"]);
+        assert_eq!(contents("...."), ["...", "."]);
+        assert_eq!(contents(".. ."), ["..", "."]);
+        assert_eq!(contents("+++"), ["++", "+"]);
+        assert_eq!(contents("a = =b"), ["a", "=", "=", "b"]);
+    }
+
+    #[test]
+    fn whole_words() {
+        assert_eq!(scan("android notable iffy"), vec![
+            token(TokenType::Identifier, "android"), token(TokenType::Identifier, "notable"), token(TokenType::Identifier, "iffy"),
+        ]);
+    }
+}
@@ -2,10 +2,10 @@ use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenType {
-    Block(Vec<Token>),
     IntLiteral,
     FloatLiteral,
     StringLiteral,
+    InterpolatedLiteral,
     BoolLiteral,
     Symbol,
     Operator,
@@ -13,11 +13,39 @@ pub enum TokenType {
     Keyword,
     Type,
     Whitespace,
+    Comment,
+    Error(String), // what's wrong with the text it covers
+    Indent, // opens a block, covering the indentation that does
+    Dedent, // closes one
     EOL,
     EOF,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TokenType::IntLiteral    => write!(f, "int literal"),
+            TokenType::FloatLiteral  => write!(f, "float literal"),
+            TokenType::StringLiteral => write!(f, "string literal"),
+            TokenType::InterpolatedLiteral => write!(f, "interpolated string"),
+            TokenType::BoolLiteral   => write!(f, "bool literal"),
+            TokenType::Symbol        => write!(f, "symbol"),
+            TokenType::Operator      => write!(f, "operator"),
+            TokenType::Identifier    => write!(f, "identifier"),
+            TokenType::Keyword       => write!(f, "keyword"),
+            TokenType::Type          => write!(f, "type"),
+            TokenType::Whitespace    => write!(f, "whitespace"),
+            TokenType::Comment       => write!(f, "comment"),
+            TokenType::Error(_)      => write!(f, "invalid token"),
+            TokenType::Indent        => write!(f, "indent"),
+            TokenType::Dedent        => write!(f, "dedent"),
+            TokenType::EOL           => write!(f, "end of line"),
+            TokenType::EOF           => write!(f, "end of file"),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct TokenPosition {
     pub line: usize,
     pub col:  usize,
@@ -46,34 +74,68 @@ impl TokenPosition {
     }
 }
 
+// the source range covered by a token or node, `end` being exclusive
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Span {
+    pub start: TokenPosition,
+    pub end:   TokenPosition,
+}
+
+impl Span {
+    pub fn new(start: TokenPosition, end: TokenPosition) -> Span {
+        Span {
+            start, end,
+        }
+    }
+
+    pub fn to(&self, other: Span) -> Span {
+        Span::new(self.start, other.end)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
+// a token's content is its text in the source, borrowed from it
 #[derive(Debug, Clone)]
-pub struct Token {
+pub struct Token<'a> {
     pub token_type: TokenType,
-    pub position:   TokenPosition,
-    content:        String,
+    pub span:       Span,
+    content:        &'a str,
 }
 
-#[allow(dead_code)]
-impl Token {
-    pub fn new(token_type: TokenType, position: TokenPosition, content: String) -> Token {
+impl<'a> Token<'a> {
+    pub fn new(token_type: TokenType, span: Span, content: &'a str) -> Token<'a> {
         Token {
             token_type,
-            position,
+            span,
             content,
         }
     }
 
-    pub fn content(&self) -> &String {
-        &self.content
+    pub fn position(&self) -> TokenPosition {
+        self.span.start
+    }
+
+    pub fn content(&self) -> &'a str {
+        self.content
     }
 }
 
-impl<'a> PartialEq for Token {
-    fn eq(&self, other: &Token) -> bool {
-        self.token_type == other.token_type
+impl<'a> fmt::Display for Token<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.token_type {
+            TokenType::Indent | TokenType::Dedent | TokenType::EOL | TokenType::EOF => write!(f, "{}", self.token_type),
+            _ => write!(f, "'{}'", self.content),
+        }
     }
+}
 
-    fn ne(&self, other: &Token) -> bool {
-        self.token_type != other.token_type
+impl<'a> PartialEq for Token<'a> {
+    fn eq(&self, other: &Token<'a>) -> bool {
+        self.token_type == other.token_type
     }
 }
@@ -1,13 +1,23 @@
 pub mod lexer;
+pub mod cst;
+pub mod format;
 pub mod parser;
 pub mod symtab;
 pub mod env;
-pub mod error;
+pub mod diagnostic;
+pub mod render;
 
-pub type RunResult<T> = Result<T, RunError>;
+pub type RunResult<T> = Result<T, Diagnostic>;
 
+// type checking carries on past errors, so it can report every one of them
+pub type CheckResult = Result<(), Vec<Diagnostic>>;
+
+#[allow(unused_imports)]
 pub use self::parser::*;
 pub use self::lexer::*;
+pub use self::cst::*;
+pub use self::format::*;
 pub use self::symtab::*;
 pub use self::env::*;
-pub use self::error::*;
+pub use self::diagnostic::*;
+pub use self::render::*;
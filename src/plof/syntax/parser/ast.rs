@@ -1,88 +1,113 @@
 use std::rc::Rc;
+use std::cell::Cell;
 
-use super::{ParserResult, ParserError};
-use super::super::{SymTab, Env};
+use super::ParserResult;
+use super::super::{SymTab, Env, CheckResult};
+use super::super::lexer::Span;
+use super::super::diagnostic::*;
 
 use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
-    Block(Rc<Vec<Statement>>),
-    NumberLiteral(f64),
-    StringLiteral(Rc<String>),
-    Identifier(Rc<String>),
-    BoolLiteral(bool),
-    DictLiteral(Rc<Vec<Expression>>),
-    Definition(Option<Type>, Rc<String>, Rc<Expression>),
-    Key(Option<Type>, Rc<String>, Rc<Expression>),
+    Block(Rc<Vec<Statement>>, Span),
+    NumberLiteral(f64, Span),
+    StringLiteral(Rc<String>, Span),
+    Interpolated {
+        parts: Rc<Vec<(Expression, Cell<bool>)>>, // the flag is set by `visit` for parts already str
+        span:  Span,
+    },
+    Identifier(Rc<String>, Span),
+    BoolLiteral(bool, Span),
+    DictLiteral(Rc<Vec<Expression>>, Span),
+    Definition {
+        t:     Option<Type>,
+        name:  Rc<String>,
+        right: Rc<Expression>,
+        local: Cell<bool>, // set by `visit`: declares a new local, or assigns an existing name
+        span:  Span,       // of the type and name
+    },
+    Key {
+        t:     Option<Type>,
+        name:  Rc<String>,
+        right: Rc<Expression>,
+        span:  Span,
+    },
     Call(Rc<Expression>, Rc<Vec<Expression>>),
+    Range(Rc<Expression>, Rc<Expression>),
     Lambda {
         name:       Option<Rc<String>>,
         retty:      Type,
         param_names: Vec<Rc<String>>,
         param_types: Vec<Type>,
+        param_spans: Vec<Span>, // of each name
         body:       Rc<Vec<Statement>>,
+        span:       Span, // of the signature
     },
-    EOF,
     Operation {
         left:  Rc<Expression>,
         op:    Operand,
         right: Rc<Expression>,
-    }
+    },
+    Unary {
+        op:   Operand,
+        expr: Rc<Expression>,
+        span: Span, // of the operator
+    },
 }
 
 impl Expression {
-    pub fn visit(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> ParserResult<()> {
+    pub fn visit(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> CheckResult {
         match *self {
-            Expression::Block(ref statements) => {
-                for s in statements.iter() {
-                    try!(s.visit(sym, env))
-                }
-
-                Ok(())
-            },
+            Expression::Block(ref statements, _) => visit_statements(statements, sym, env),
 
-            Expression::Identifier(ref id) => match sym.get_name(&*id) {
+            Expression::Identifier(ref id, span) => match sym.get_name(id) {
                 Some(_) => {
                     Ok(())
                 },
-                None => Err(ParserError::new(&format!("use of undeclared: {}", id))),
+                None => Err(vec![undeclared(id, span, sym)]),
             },
 
-            Expression::DictLiteral(ref body) => {
-                let local_sym = Rc::new(SymTab::new(sym.clone(), &vec!()));
-                let local_env = Rc::new(Env::new(env.clone(), &vec!()));
+            Expression::DictLiteral(ref body, _) => {
+                let local_sym = Rc::new(SymTab::new(sym.clone(), &[]));
+                let local_env = Rc::new(Env::new(env.clone(), &[]));
+
+                let mut errors = Vec::new();
 
                 for s in body.iter() {
-                    try!(s.visit(&local_sym, &local_env))
+                    if let Err(e) = s.visit(&local_sym, &local_env) {
+                        errors.extend(e)
+                    }
                 }
 
-                Ok(())
+                checked(errors)
             },
             
-            Expression::Key(ref t, ref name, ref expr) => {
-                expr.visit(sym, env)?;
-                
+            Expression::Key {
+                ref t, ref name, ref right, span,
+            } => {
+                let mut errors = Vec::new();
+
+                let found = visit_typed(right, sym, env, &mut errors);
+
                 let tp = match *t {
                     Some(ref tt) => {
-                        if !tt.compare(&expr.get_type(sym, env)?) {
-                            return Err(ParserError::new(&format!("right-hand doesn't match type of: {}", name)))
+                        if !tt.compare(&found) {
+                            errors.push(mismatch(name, tt, &found, span, right.span()))
                         }
                         tt.clone()
                     },
                     None => Type::Any,
                 };
                 
-                match sym.get_name(&name) {
-                    Some((i, env_index)) => {
-                        match env.get_type(i, env_index) {
-                            Ok(tp2) => if !tp2.compare(&tp) {
-                                return Err(ParserError::new(&format!("can't change type of '{}'!", name)))
-                            },
-                            Err(e) => return Err(ParserError::new(&format!("{}", e))),
-                        }
-                    },
-                    None => (),
+                if let Some((i, env_index)) = sym.get_name(name) {
+                    let tp2 = env.get_type(i, env_index)?;
+
+                    if !tp2.compare(&tp) {
+                        errors.push(changed_type(name, &tp2, &tp, right.span()))
+                    }
+
+                    return checked(errors)
                 }
 
                 let index = sym.add_name(name);
@@ -90,167 +115,251 @@ impl Expression {
                     env.grow();
                 }
 
-                if let Err(e) = env.set_type(index, 0, tp) {
-                    Err(ParserError::new(&format!("error setting type: {}", e)))
-                } else {
-                    Ok(())
-                }
+                env.set_type(index, 0, tp)?;
+
+                checked(errors)
             },
 
-            Expression::Definition(ref t, ref name, ref expr) => {
-                expr.visit(sym, env)?;
+            Expression::Definition {
+                ref t, ref name, ref right, ref local, span,
+            } => {
+                let mut errors = Vec::new();
+
+                let found = visit_typed(right, sym, env, &mut errors);
 
                 let tp = match *t {
                     Some(ref tt) => {
-                        if tt.compare(&expr.get_type(sym, env)?) {
-                            return Err(ParserError::new(&format!("right-hand doesn't match type of: {}", name)))
+                        if !tt.compare(&found) {
+                            errors.push(mismatch(name, tt, &found, span, right.span()))
                         }
                         tt.clone()
                     },
                     None => Type::Any,
                 };
 
-                match sym.get_name(&name) {
-                    Some((i, env_index)) => {
-                        match env.get_type(i, env_index) {
-                            Ok(tp2) => if !tp2.compare(&tp) {
-                                return Err(ParserError::new(&format!("can't change type of '{}'!", name)))
-                            },
-                            Err(e) => return Err(ParserError::new(&format!("{}", e))),
-                        }
-                    },
-                    None => (),
+                if let Some((i, env_index)) = sym.get_name(name) {
+                    let tp2 = env.get_type(i, env_index)?;
+
+                    if !tp2.compare(t.as_ref().unwrap_or(&found)) {
+                        errors.push(changed_type(name, &tp2, t.as_ref().unwrap_or(&found), right.span()))
+                    }
+
+                    // assigning to an existing name, in this or an enclosing scope
+                    local.set(false);
+
+                    if t.is_some() {
+                        env.set_type(i, env_index, tp)?
+                    }
+
+                    return checked(errors)
                 }
 
+                local.set(true);
+
+                // declared even when the right-hand failed, so later uses don't pile up errors
                 let index = sym.add_name(name);
                 if index >= env.size() {
                     env.grow();
                 }
 
-                if let Err(e) = env.set_type(index, 0, tp) {
-                    Err(ParserError::new(&format!("error setting type: {}", e)))
-                } else {
-                    Ok(())
-                }
+                env.set_type(index, 0, tp)?;
+
+                checked(errors)
             },
 
             Expression::Lambda {
-                ref name, ref retty, ref param_names, ref param_types, ref body,
+                ref name, ref retty, ref param_names, ref param_types, ref body, span, ..
             } => {
-                if let &Some(ref n) = name {
-                    match sym.get_name(&n) {
-                        Some((_, _)) => return Err(ParserError::new(&format!("can't redefine lambda '{}'!", n))),
+                let mut errors = Vec::new();
+
+                if let Some(ref n) = *name {
+                    match sym.get_name(n) {
+                        Some((_, _)) => errors.push(Diagnostic::error(E_REDEFINED, &format!("can't redefine lambda '{}'", n))
+                                                    .with_label(span, "defined again here")),
                         None => {
-                            let index = sym.add_name(&n);
+                            let index = sym.add_name(n);
                             if index >= env.size() {
                                 env.grow();
                             }
 
-                            if let Err(e) = env.set_type(index, 0, try!(self.get_type(sym, env))) {
-                                panic!("error setting type: {}", e)
-                            }
+                            env.set_type(index, 0, self.get_type(sym, env)?)?
                         },
                     }
                 }
 
-                let local_sym = Rc::new(SymTab::new(sym.clone(), &param_names));
-                let local_env = Rc::new(Env::new(env.clone(), &param_types));
+                let local_sym = Rc::new(SymTab::new(sym.clone(), param_names));
+                let local_env = Rc::new(Env::new(env.clone(), param_types));
 
                 for statement in body.iter() {
-                    try!(statement.visit(&local_sym, &local_env));
+                    if let Err(e) = statement.visit(&local_sym, &local_env) {
+                        errors.extend(e);
+                        continue
+                    }
 
-                    if try!(statement.get_type(&local_sym, &local_env)) != *retty {
-                        match *retty {
-                            Type::Any => (),
-                            _ => return Err(ParserError::new(&format!("lambda must return return-type"))),
-                        }
+                    let found = match statement.get_type(&local_sym, &local_env) {
+                        Ok(t)  => t,
+                        Err(e) => {
+                            errors.push(e);
+                            continue
+                        },
+                    };
+
+                    if !found.compare(retty) {
+                        errors.push(Diagnostic::error(E_RETURN_TYPE, "lambda must return return-type")
+                                    .with_label(statement.span(), &format!("expected {}, found {}", retty, found))
+                                    .with_secondary(span, &format!("lambda returns {}", retty)))
                     }
                 }
 
-                Ok(())
+                checked(errors)
             },
 
             Expression::Call(ref id, ref args) => {
-                match try!(id.get_type(sym, env)) {
+                let mut errors = Vec::new();
+
+                let arg_types: Vec<Type> = args.iter().map(|arg| visit_typed(arg, sym, env, &mut errors)).collect();
+
+                match id.get_type(sym, env)? {
                     Type::Lambda(ref params) => {
-                        let mut arg_types = Vec::new();
+                        let (fixed, many) = match params[params.len() - 1] {
+                            Type::Many(ref t) => (&params[1 .. params.len() - 1], Some(&**t)),
+                            _                 => (&params[1 ..], None),
+                        };
+
+                        if arg_types.len() < fixed.len() || (many.is_none() && arg_types.len() > fixed.len()) {
+                            let at_least = if many.is_some() { "at least " } else { "" };
+                            let plural   = if fixed.len() == 1 { "" } else { "s" };
 
-                        for arg in args.iter() {
-                            arg_types.push(try!(arg.get_type(sym, env)));
+                            errors.push(Diagnostic::error(E_BAD_ARGS, &format!("expected {}{} argument{}, found {}", at_least, fixed.len(), plural, arg_types.len()))
+                                        .with_span(self.span())
+                                        .with_note(&format!("'{}' is {}", id, Type::Lambda(params.clone()))));
+
+                            return Err(errors)
                         }
 
-                        match params[params.len() - 1] {
-                            Type::Many(ref t) => {
-                                if params[1..params.len() - 1].to_vec() != arg_types.as_slice()[1 .. params.len() - 1].to_vec() {
-                                    Err(ParserError::new(&format!("can't invoke lambda with bad args!")))
-                                } else {
-                                    for arg_t in arg_types[params.len() - 1 ..].iter() {
-                                        if !arg_t.compare(&**t) {
-                                            return Err(ParserError::new(&format!("expected '{:?}' found '{:?}'", t, arg_t)))
-                                        }
-                                    }
-                                    Ok(())
-                                }
-                            },
-                            _ => if params[1..].to_vec() != arg_types.as_slice() {
-                                Err(ParserError::new(&format!("can't invoke lambda with bad args!")))
-                            } else {
-                                Ok(())
-                            },
+                        for (i, found) in arg_types.iter().enumerate() {
+                            let expected = fixed.get(i).or(many).unwrap();
+
+                            if !found.compare(expected) {
+                                errors.push(Diagnostic::error(E_BAD_ARGS, "can't invoke lambda with bad args")
+                                            .with_label(args[i].span(), &format!("expected {}, found {}", expected, found))
+                                            .with_note(&format!("'{}' is {}", id, Type::Lambda(params.clone()))))
+                            }
                         }
+
+                        checked(errors)
                     },
 
-                    Type::Any => Ok(()),
+                    Type::Any => checked(errors),
+
+                    t => {
+                        errors.insert(0, Diagnostic::error(E_NOT_CALLABLE, &format!("can't call non-lambda: {}", id))
+                                         .with_label(id.span(), &format!("this is {}", t)));
 
-                    _ => Err(ParserError::new(&format!("can't call non-lambda: {}", id))),
+                        Err(errors)
+                    },
                 }
             }
 
+            Expression::Range(ref start, ref end) => {
+                let mut errors = Vec::new();
+
+                for bound in &[start, end] {
+                    match visit_typed(bound, sym, env, &mut errors) {
+                        Type::Num | Type::Any => (),
+                        t => errors.push(Diagnostic::error(E_RANGE, "range bounds must be num")
+                                         .with_label(bound.span(), &format!("this is {}", t))),
+                    }
+                }
+
+                checked(errors)
+            },
+
+            Expression::Operation {
+                ref left, ref right, ..
+            } => {
+                let mut errors = Vec::new();
+
+                if let Err(e) = left.visit(sym, env) {
+                    errors.extend(e)
+                }
+
+                if let Err(e) = right.visit(sym, env) {
+                    errors.extend(e)
+                }
+
+                // a broken operand would only be reported again by `get_type`
+                if errors.is_empty() {
+                    self.get_type(sym, env)?;
+                }
+
+                checked(errors)
+            },
+
+            Expression::Unary {
+                ref expr, ..
+            } => {
+                expr.visit(sym, env)?;
+                self.get_type(sym, env)?;
+
+                Ok(())
+            },
+
+            // anything goes into a string, through 'tostring' unless it's one already
+            Expression::Interpolated {
+                ref parts, ..
+            } => {
+                let mut errors = Vec::new();
+
+                for (part, is_str) in parts.iter() {
+                    is_str.set(visit_typed(part, sym, env, &mut errors) == Type::Str)
+                }
+
+                checked(errors)
+            },
+
             _ => Ok(()),
         }
     }
 
     pub fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> ParserResult<Type> {
         match *self {
-            Expression::Block(ref statements) => {
+            Expression::Block(ref statements, _) => {
                 for s in statements.iter() {
-                    match *s {
-                        Statement::Return(ref ret) => match *ret {
-                            Some(ref expr) => return Ok(try!(expr.get_type(sym, env))),
-                            None => (),
-                        },
-
-                        _ => (),
+                    if let Statement::Return(Some(ref expr), _) = *s {
+                        return expr.get_type(sym, env)
                     }
                 }
 
                 match statements.last() {
-                    Some(e) => Ok(try!(e.get_type(sym, env))),
+                    Some(e) => Ok(e.get_type(sym, env)?),
                     None    => Ok(Type::Nil),
                 }
             },
 
-            Expression::NumberLiteral(_)  => Ok(Type::Num),
-            Expression::StringLiteral(_)  => Ok(Type::Str),
-            Expression::BoolLiteral(_)    => Ok(Type::Bool),
-            Expression::Identifier(ref n) => match sym.get_name(&*n) {
+            Expression::NumberLiteral(..)  => Ok(Type::Num),
+            Expression::StringLiteral(..)  => Ok(Type::Str),
+            Expression::Interpolated { .. } => Ok(Type::Str),
+            Expression::BoolLiteral(..)    => Ok(Type::Bool),
+            Expression::Identifier(ref n, span) => match sym.get_name(n) {
                 Some((i, env_index)) => {
                     Ok(env.get_type(i, env_index).unwrap())
                 },
-                None => Err(ParserError::new(&format!("can't get type of undeclared: {}", n))),
+                None => Err(undeclared(n, span, sym)),
             },
 
-            Expression::Definition(ref t, _, ref expr) => {
-                match *t {
-                    Some(ref tp) => return Ok(tp.clone()),
-                    None     => (),
+            Expression::Definition {
+                ref t, ref right, ..
+            } => {
+                if let Some(ref tp) = *t {
+                    return Ok(tp.clone())
                 }
 
-                Ok(try!(expr.get_type(sym, env)))
+                Ok(right.get_type(sym, env)?)
             },
 
             Expression::Lambda {
-                ref name, ref retty, ref param_names, ref param_types, ref body,
+                ref retty, ref param_types, ..
             } => {
                 let mut tp = vec![retty.clone()];
 
@@ -261,20 +370,38 @@ impl Expression {
                 Ok(Type::Lambda(Rc::new(tp)))
             },
 
-            Expression::Call(ref id, ref args) => {
-                match try!(id.get_type(sym, env)) {
+            Expression::Call(ref id, _) => {
+                match id.get_type(sym, env)? {
                     Type::Lambda(ref params) => {
-                        Ok(params.get(0).unwrap().clone())
+                        Ok(params[0].clone())
                     },
                     Type::Any => Ok(Type::Any),
-                    _ => Err(ParserError::new(&format!("can't call non-lambda: {}", id))),
+                    t => Err(Diagnostic::error(E_NOT_CALLABLE, &format!("can't call non-lambda: {}", id))
+                             .with_label(id.span(), &format!("this is {}", t))),
                 }
             },
 
             Expression::Operation {
                 ref left, ref op, ref right,
             } => {
-                Ok(try!(op.operate((try!(left.get_type(sym, env)), try!(right.get_type(sym, env))))))
+                let lr = (left.get_type(sym, env)?, right.get_type(sym, env)?);
+
+                op.operate(lr.clone()).map_err(|e| {
+                    e.with_span(self.span())
+                     .with_secondary(left.span(), &format!("this is {}", lr.0))
+                     .with_secondary(right.span(), &format!("this is {}", lr.1))
+                })
+            },
+
+            Expression::Unary {
+                ref op, ref expr, ..
+            } => {
+                let t = expr.get_type(sym, env)?;
+
+                op.operate_unary(t.clone()).map_err(|e| {
+                    e.with_span(self.span())
+                     .with_secondary(expr.span(), &format!("this is {}", t))
+                })
             },
 
             _ => Ok(Type::Undefined),
@@ -283,20 +410,45 @@ impl Expression {
 
     pub fn translate_lua(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
-            Expression::Block(ref statements) => {
+            Expression::Block(ref statements, _) => {
                 for s in statements.iter() {
                     s.translate_lua(f)?;
                 }
 
                 Ok(())
             },
-            Expression::NumberLiteral(ref n) => write!(f, "{}", n),
-            Expression::StringLiteral(ref n) => write!(f, "\"{}\"", n),
-            Expression::BoolLiteral(ref n)   => write!(f, "{}", n),
-            Expression::Identifier(ref n)    => write!(f, "{}", n),
-            Expression::Definition(_, ref name, ref expr) => writeln!(f, "local {} = {};", name, expr),
-            Expression::Key(_, ref name, ref expr)        => write!(f, "{} = {}", name, expr),
-            Expression::DictLiteral(ref body)  => {
+            Expression::NumberLiteral(ref n, _) => write!(f, "{}", lua_number(*n)),
+            Expression::StringLiteral(ref n, _) => write!(f, "\"{}\"", escape_string(n)),
+            Expression::Interpolated { ref parts, .. } => {
+                write!(f, "(")?;
+
+                for (i, (part, is_str)) in parts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " .. ")?;
+                    }
+
+                    if is_str.get() {
+                        write!(f, "{}", part)?;
+                    } else {
+                        write!(f, "tostring({})", part)?;
+                    }
+                }
+
+                write!(f, ")")
+            },
+            Expression::BoolLiteral(ref n, _)   => write!(f, "{}", n),
+            Expression::Identifier(ref n, _)    => write!(f, "{}", n),
+            Expression::Definition {
+                ref name, ref right, ref local, ..
+            } => if local.get() {
+                write!(f, "local {} = {};", name, right)
+            } else {
+                write!(f, "{} = {};", name, right)
+            },
+            Expression::Key {
+                ref name, ref right, ..
+            } => write!(f, "{} = {}", name, right),
+            Expression::DictLiteral(ref body, _) => {
                 write!(f, "{{")?;
                 
                 for e in body.iter() {
@@ -309,24 +461,21 @@ impl Expression {
                 write!(f, "{}", id)?;
                 write!(f, "(")?;
 
-                let mut acc = 1;
-                for e in args.iter() {
+                for (i, e) in args.iter().enumerate() {
                     write!(f, "({})", e)?;
-                    if acc != args.len() {
+                    if i + 1 != args.len() {
                         write!(f, ",")?;
                     }
-                    acc += 1;
                 }
 
                 write!(f, ")")
             },
             Expression::Lambda {
-                ref name, ref retty, ref param_names, ref param_types, ref body,
+                ref name, ref param_names, ref body, ..
             } => {
                 write!(f, "function ")?;
-                match *name {
-                    Some(ref n) => { write!(f, "{}", n)?; },
-                    None        => (),
+                if let Some(ref n) = *name {
+                    write!(f, "{}", n)?;
                 }
                 
                 write!(f, "(")?;
@@ -338,18 +487,9 @@ impl Expression {
                     }
                 }
                 
-                write!(f, ")")?;
+                writeln!(f, ")")?;
                 
-                for s in body.iter() {
-                    if s == body.last().unwrap() {
-                        match *s {
-                            Statement::Expression(ref e) => { write!(f, "return ({});\n", e)?; },
-                            _ => { write!(f, "{}", s)?; },
-                        }
-                    } else {
-                        write!(f, "{}", s)?;
-                    }
-                }
+                translate_body(f, body, true)?;
                 
                 write!(f, "end")
             },
@@ -357,11 +497,13 @@ impl Expression {
             Expression::Operation {
                 ref left, ref op, ref right,
             } => {
-                write!(f, "(");
-                write!(f, "({})", left);
-                write!(f, "{}", op);
-                write!(f, "({})", right);
-                write!(f, ")")
+                write!(f, "(({}){}({}))", left, op, right)
+            },
+
+            Expression::Unary {
+                ref op, ref expr, ..
+            } => {
+                write!(f, "({} ({}))", op, expr)
             },
 
             _ => Ok(()),
@@ -369,6 +511,92 @@ impl Expression {
     }
 }
 
+impl Expression {
+    // whether lua can give it back with 'return', unlike a definition or a named function
+    fn is_value(&self) -> bool {
+        match *self {
+            Expression::Block(..) |
+            Expression::Definition { .. } | Expression::Key { .. } => false,
+            Expression::Lambda { ref name, .. } => name.is_none(),
+            _ => true,
+        }
+    }
+
+    // calls `f` on it and on everything in it, going inside only what `f` gives true for
+    fn walk<F: FnMut(&Expression) -> bool>(&self, f: &mut F) {
+        if !f(self) {
+            return
+        }
+
+        match *self {
+            Expression::Block(ref body, _) |
+            Expression::Lambda { ref body, .. } => walk_body(body, f),
+
+            Expression::Interpolated { ref parts, .. } => for (part, _) in parts.iter() {
+                part.walk(f)
+            },
+
+            Expression::DictLiteral(ref body, _) => for e in body.iter() {
+                e.walk(f)
+            },
+
+            Expression::Definition { ref right, .. } |
+            Expression::Key { ref right, .. } => right.walk(f),
+
+            Expression::Call(ref id, ref args) => {
+                id.walk(f);
+
+                for arg in args.iter() {
+                    arg.walk(f)
+                }
+            },
+
+            Expression::Range(ref left, ref right) |
+            Expression::Operation { ref left, ref right, .. } => {
+                left.walk(f);
+                right.walk(f)
+            },
+
+            Expression::Unary { ref expr, .. } => expr.walk(f),
+
+            Expression::NumberLiteral(..) | Expression::StringLiteral(..) |
+            Expression::Identifier(..) | Expression::BoolLiteral(..) => (),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match *self {
+            Expression::Block(_, span) |
+            Expression::NumberLiteral(_, span) |
+            Expression::StringLiteral(_, span) |
+            Expression::Interpolated { span, .. } |
+            Expression::Identifier(_, span) |
+            Expression::BoolLiteral(_, span) |
+            Expression::DictLiteral(_, span) |
+            Expression::Lambda { span, .. } => span,
+
+            Expression::Definition { span, ref right, .. } |
+            Expression::Key { span, ref right, .. } => span.to(right.span()),
+
+            Expression::Call(ref id, ref args) => match args.last() {
+                Some(last) => id.span().to(last.span()),
+                None       => id.span(),
+            },
+
+            Expression::Range(ref start, ref end) => start.span().to(end.span()),
+
+            Expression::Operation {
+                ref left, ref right, ..
+            } => left.span().to(right.span()),
+
+            Expression::Unary {
+                ref expr, span, ..
+            } => span.to(expr.span()),
+
+        }
+    }
+}
+
 impl fmt::Display for Expression {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.translate_lua(f)
@@ -378,44 +606,460 @@ impl fmt::Display for Expression {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Statement {
     Expression(Rc<Expression>),
-    Return(Option<Rc<Expression>>),
+    Return(Option<Rc<Expression>>, Span), // of the 'return'
+    If {
+        condition: Rc<Expression>,
+        body:      Rc<Vec<Statement>>,
+        elifs:     Vec<(Rc<Expression>, Rc<Vec<Statement>>)>,
+        orelse:    Option<Rc<Vec<Statement>>>,
+    },
+    Unless {
+        condition: Rc<Expression>,
+        body:      Rc<Vec<Statement>>,
+        orelse:    Option<Rc<Vec<Statement>>>,
+    },
+    While {
+        condition: Rc<Expression>,
+        body:      Rc<Vec<Statement>>,
+    },
+    For {
+        names:    Vec<Rc<String>>,
+        iterator: Rc<Expression>,
+        body:     Rc<Vec<Statement>>,
+    },
 }
 
 impl Statement {
-    pub fn visit(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> ParserResult<()> {
+    pub fn visit(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> CheckResult {
         match *self {
             Statement::Expression(ref e) => e.visit(sym, env),
-            Statement::Return(ref e) => match *e {
+            Statement::Return(ref e, _) => match *e {
                 Some(ref expr) => expr.visit(sym, env),
                 None           => Ok(()),
             },
-            _ => Err(ParserError::new("unimplemented visitor"))
+
+            Statement::If {
+                ref condition, ref body, ref elifs, ref orelse,
+            } => {
+                let mut errors = Vec::new();
+
+                visit_condition(condition, sym, env, &mut errors);
+                visit_body(body, sym, env, &mut errors);
+
+                for (condition, body) in elifs.iter() {
+                    visit_condition(condition, sym, env, &mut errors);
+                    visit_body(body, sym, env, &mut errors);
+                }
+
+                if let Some(ref body) = *orelse {
+                    visit_body(body, sym, env, &mut errors);
+                }
+
+                checked(errors)
+            },
+
+            Statement::Unless {
+                ref condition, ref body, ref orelse,
+            } => {
+                let mut errors = Vec::new();
+
+                visit_condition(condition, sym, env, &mut errors);
+                visit_body(body, sym, env, &mut errors);
+
+                if let Some(ref body) = *orelse {
+                    visit_body(body, sym, env, &mut errors);
+                }
+
+                checked(errors)
+            },
+
+            Statement::While {
+                ref condition, ref body,
+            } => {
+                let mut errors = Vec::new();
+
+                visit_condition(condition, sym, env, &mut errors);
+                visit_body(body, sym, env, &mut errors);
+
+                checked(errors)
+            },
+
+            Statement::For {
+                ref names, ref iterator, ref body,
+            } => {
+                let mut errors = Vec::new();
+
+                let types = match **iterator {
+                    Expression::Range(..) => {
+                        if let Err(e) = iterator.visit(sym, env) {
+                            errors.extend(e)
+                        }
+
+                        vec![Type::Num]
+                    },
+
+                    _ => match visit_typed(iterator, sym, env, &mut errors) {
+                        Type::Any | Type::Undefined => vec![Type::Any; names.len()],
+                        t => {
+                            errors.push(Diagnostic::error(E_NOT_ITERABLE, &format!("can't iterate over {}", t))
+                                        .with_label(iterator.span(), &format!("this is {}", t))
+                                        .with_note("only tables and ranges, like '1..10', can be iterated"));
+
+                            vec![Type::Any; names.len()]
+                        },
+                    },
+                };
+
+                let local_sym = Rc::new(SymTab::new(sym.clone(), names));
+                let local_env = Rc::new(Env::new(env.clone(), &types));
+
+                if let Err(e) = visit_statements(body, &local_sym, &local_env) {
+                    errors.extend(e)
+                }
+
+                checked(errors)
+            },
         }
     }
 
     pub fn get_type(&self, sym: &Rc<SymTab>, env: &Rc<Env>) -> ParserResult<Type> {
         match *self {
             Statement::Expression(ref e) => e.get_type(sym, env),
-            Statement::Return(ref e) => match *e {
+            Statement::Return(ref e, _) => match *e {
                 Some(ref expr) => expr.get_type(sym, env),
                 None           => Ok(Type::Nil),
             },
-            _ => Err(ParserError::new("unimplemented visitor"))
+
+            // branches and loop bodies may or may not be taken
+            Statement::If { .. } | Statement::Unless { .. } |
+            Statement::While { .. } | Statement::For { .. } => Ok(Type::Any),
+        }
+    }
+
+    fn walk<F: FnMut(&Expression) -> bool>(&self, f: &mut F) {
+        match *self {
+            Statement::Expression(ref e) |
+            Statement::Return(Some(ref e), _) => e.walk(f),
+            Statement::Return(None, _) => (),
+
+            Statement::If {
+                ref condition, ref body, ref elifs, ref orelse,
+            } => {
+                condition.walk(f);
+                walk_body(body, f);
+
+                for (condition, body) in elifs.iter() {
+                    condition.walk(f);
+                    walk_body(body, f)
+                }
+
+                if let Some(ref body) = *orelse {
+                    walk_body(body, f)
+                }
+            },
+
+            Statement::Unless {
+                ref condition, ref body, ref orelse,
+            } => {
+                condition.walk(f);
+                walk_body(body, f);
+
+                if let Some(ref body) = *orelse {
+                    walk_body(body, f)
+                }
+            },
+
+            Statement::While {
+                ref condition, ref body,
+            } => {
+                condition.walk(f);
+                walk_body(body, f)
+            },
+
+            Statement::For {
+                ref iterator, ref body, ..
+            } => {
+                iterator.walk(f);
+                walk_body(body, f)
+            },
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match *self {
+            Statement::Expression(ref e) => e.span(),
+            Statement::Return(ref e, span) => match *e {
+                Some(ref expr) => expr.span(),
+                None           => span,
+            },
+
+            Statement::If { ref condition, .. } |
+            Statement::Unless { ref condition, .. } |
+            Statement::While { ref condition, .. } => condition.span(),
+
+            Statement::For { ref iterator, .. } => iterator.span(),
         }
     }
 
     pub fn translate_lua(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.translate(f, false)
+    }
+
+    // `tail` being whether it ends a function, which gives back its last value, however
+    // many branches of an 'if' or 'unless' that's in
+    fn translate(&self, f: &mut fmt::Formatter, tail: bool) -> fmt::Result {
         match *self {
+            Statement::Expression(ref e) if tail && e.is_value() => write!(f, "return ({});", e),
             Statement::Expression(ref e) => e.translate_lua(f),
-            Statement::Return(ref e)     => match *e {
-                Some(ref expr) => write!(f, "{}", format!("return ({});", expr)),
+            Statement::Return(ref e, _)  => match *e {
+                Some(ref expr) => write!(f, "return ({});", expr),
                 None => write!(f, "return;")
             },
-            _ => Ok(()),
+
+            Statement::If {
+                ref condition, ref body, ref elifs, ref orelse,
+            } => {
+                writeln!(f, "if ({}) then", condition)?;
+                translate_body(f, body, tail)?;
+
+                for (condition, body) in elifs.iter() {
+                    writeln!(f, "elseif ({}) then", condition)?;
+                    translate_body(f, body, tail)?;
+                }
+
+                if let Some(ref body) = *orelse {
+                    writeln!(f, "else")?;
+                    translate_body(f, body, tail)?;
+                }
+
+                write!(f, "end")
+            },
+
+            Statement::Unless {
+                ref condition, ref body, ref orelse,
+            } => {
+                writeln!(f, "if not ({}) then", condition)?;
+                translate_body(f, body, tail)?;
+
+                if let Some(ref body) = *orelse {
+                    writeln!(f, "else")?;
+                    translate_body(f, body, tail)?;
+                }
+
+                write!(f, "end")
+            },
+
+            Statement::While {
+                ref condition, ref body,
+            } => {
+                writeln!(f, "while ({}) do", condition)?;
+                translate_body(f, body, false)?;
+                write!(f, "end")
+            },
+
+            Statement::For {
+                ref names, ref iterator, ref body,
+            } => {
+                match **iterator {
+                    Expression::Range(ref start, ref end) => writeln!(f, "for {} = ({}), ({}) do", names[0], start, end)?,
+                    _ => {
+                        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+                        writeln!(f, "for {} in pairs({}) do", names.join(", "), iterator)?
+                    },
+                }
+
+                translate_body(f, body, false)?;
+                write!(f, "end")
+            },
+        }
+    }
+}
+
+// type checks statements one after another, carrying on past the ones that fail
+pub fn visit_statements(body: &[Statement], sym: &Rc<SymTab>, env: &Rc<Env>) -> CheckResult {
+    let mut errors = Vec::new();
+
+    for s in body.iter() {
+        if let Err(e) = s.visit(sym, env) {
+            errors.extend(e)
+        }
+    }
+
+    checked(errors)
+}
+
+fn walk_body<F: FnMut(&Expression) -> bool>(body: &[Statement], f: &mut F) {
+    for s in body.iter() {
+        s.walk(f)
+    }
+}
+
+// warns of function parameters and locals nothing reads, after `visit` has told which
+// definitions are locals; a name starting with '_' is meant to go unread
+pub fn unused_names(body: &[Statement]) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+
+    walk_body(body, &mut |e| {
+        if let Expression::Lambda { ref param_names, ref param_spans, ref body, .. } = *e {
+            let reads = |name: &str| {
+                let mut read = false;
+
+                walk_body(body, &mut |e| {
+                    if let Expression::Identifier(ref n, _) = *e {
+                        read |= n.as_str() == name
+                    }
+
+                    !read
+                });
+
+                read
+            };
+
+            for (name, &span) in param_names.iter().zip(param_spans.iter()) {
+                if !name.starts_with('_') && !reads(name) {
+                    warnings.push(unused(&format!("unused parameter '{}'", name), name, span))
+                }
+            }
+
+            // a nested function's locals are its own, and get looked at when it's reached
+            let mut locals = Vec::new();
+
+            walk_body(body, &mut |e| match *e {
+                Expression::Definition { ref name, ref local, span, .. } => {
+                    if local.get() {
+                        locals.push((name.clone(), span))
+                    }
+                    true
+                },
+                Expression::Lambda { .. } => false,
+                _ => true,
+            });
+
+            for (name, span) in locals {
+                if !name.starts_with('_') && !reads(&name) {
+                    warnings.push(unused(&format!("unused variable '{}'", name), &name, span))
+                }
+            }
         }
+
+        true
+    });
+
+    warnings
+}
+
+// visits an expression and returns its type, falling back to `any` when it's broken
+fn visit_typed(expr: &Expression, sym: &Rc<SymTab>, env: &Rc<Env>, errors: &mut Vec<Diagnostic>) -> Type {
+    if let Err(e) = expr.visit(sym, env) {
+        errors.extend(e);
+
+        // a lambda still has its signature, even with a broken body
+        return expr.get_type(sym, env).unwrap_or(Type::Any)
+    }
+
+    match expr.get_type(sym, env) {
+        Ok(t)  => t,
+        Err(e) => {
+            errors.push(e);
+            Type::Any
+        },
+    }
+}
+
+fn checked(errors: Vec<Diagnostic>) -> CheckResult {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
     }
 }
 
+fn visit_condition(condition: &Expression, sym: &Rc<SymTab>, env: &Rc<Env>, errors: &mut Vec<Diagnostic>) {
+    match visit_typed(condition, sym, env, errors) {
+        Type::Bool | Type::Any => (),
+        t => errors.push(Diagnostic::error(E_CONDITION, "condition must be bool")
+                         .with_label(condition.span(), &format!("this is {}", t))),
+    }
+}
+
+fn visit_body(body: &[Statement], sym: &Rc<SymTab>, env: &Rc<Env>, errors: &mut Vec<Diagnostic>) {
+    let local_sym = Rc::new(SymTab::new(sym.clone(), &[]));
+    let local_env = Rc::new(Env::new(env.clone(), &[]));
+
+    if let Err(e) = visit_statements(body, &local_sym, &local_env) {
+        errors.extend(e)
+    }
+}
+
+fn undeclared(name: &str, span: Span, sym: &SymTab) -> Diagnostic {
+    let d = Diagnostic::error(E_UNDECLARED, &format!("use of undeclared: {}", name)).with_label(span, "not found in this scope");
+
+    match sym.similar_name(name) {
+        Some(similar) => d.with_suggestion(&format!("did you mean '{}'?", similar)),
+        None          => d,
+    }
+}
+
+fn unused(message: &str, name: &str, span: Span) -> Diagnostic {
+    Diagnostic::warning(W_UNUSED, message)
+        .with_span(span)
+        .with_note(&format!("if that's meant, call it '_{}'", name))
+}
+
+fn mismatch(name: &str, expected: &Type, found: &Type, span: Span, right: Span) -> Diagnostic {
+    Diagnostic::error(E_MISMATCH, &format!("right-hand doesn't match type of: {}", name))
+        .with_label(right, &format!("expected {}, found {}", expected, found))
+        .with_secondary(span, &format!("'{}' is declared {} here", name, expected))
+}
+
+fn changed_type(name: &str, was: &Type, now: &Type, right: Span) -> Diagnostic {
+    Diagnostic::error(E_CHANGED_TYPE, &format!("can't change type of '{}'", name))
+        .with_label(right, &format!("expected {}, found {}", was, now))
+        .with_note(&format!("'{}' has type {}", name, was))
+}
+
+// whole numbers as lua integers, the rest in the shortest form reading back the same
+fn lua_number(n: f64) -> String {
+    if n.is_infinite() {
+        if n > 0.0 { "math.huge".to_owned() } else { "-math.huge".to_owned() }
+    } else if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n)
+    } else {
+        format!("{:?}", n)
+    }
+}
+
+// a lua string literal's body, holding exactly the characters of `s`
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"'  => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+
+            // three digits always, so a digit following isn't read into the escape
+            c if c.is_ascii_control() => escaped.push_str(&format!("\\{:03}", c as u32)),
+
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn translate_body(f: &mut fmt::Formatter, body: &[Statement], tail: bool) -> fmt::Result {
+    for (i, s) in body.iter().enumerate() {
+        s.translate(f, tail && i + 1 == body.len())?;
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
 impl fmt::Display for Statement {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.translate_lua(f)
@@ -437,6 +1081,32 @@ impl Type {
     }
 }
 
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Type::Str       => write!(f, "str"),
+            Type::Num       => write!(f, "num"),
+            Type::Bool      => write!(f, "bool"),
+            Type::Any       => write!(f, "any"),
+            Type::Nil       => write!(f, "nil"),
+            Type::Undefined => write!(f, "undefined"),
+            Type::Many(ref t) => write!(f, "{}...", t),
+            Type::Lambda(ref t) => {
+                write!(f, "{} (", t[0])?;
+
+                for (i, param) in t[1..].iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", param)?;
+                }
+
+                write!(f, ")")
+            },
+        }
+    }
+}
+
 pub fn get_type(v: &str) -> Option<Type> {
     match v {
         "str"  => Some(Type::Str),
@@ -453,12 +1123,58 @@ pub enum Operand {
     Pow,
     Mul, Div, Mod,
     Add, Sub,
+    Concat,
     Equal, NEqual,
     Lt, Gt, LtEqual, GtEqual,
     And, Or, Not,
+    Neg, Len,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Associativity {
+    Left,
+    Right,
 }
 
 impl Operand {
+    // binding power, higher binding tighter, after lua's own table
+    pub fn precedence(&self) -> (u8, Associativity) {
+        match *self {
+            Operand::Or => (1, Associativity::Left),
+            Operand::And => (2, Associativity::Left),
+
+            Operand::Equal | Operand::NEqual |
+            Operand::Lt | Operand::Gt | Operand::LtEqual | Operand::GtEqual => (3, Associativity::Left),
+
+            Operand::Concat => (4, Associativity::Right),
+            Operand::Add | Operand::Sub => (5, Associativity::Left),
+            Operand::Mul | Operand::Div | Operand::Mod => (6, Associativity::Left),
+            Operand::Not | Operand::Neg | Operand::Len => (7, Associativity::Right),
+            Operand::Pow => (8, Associativity::Right),
+        }
+    }
+
+    pub fn operate_unary(&self, t: Type) -> ParserResult<Type> {
+        match *self {
+            Operand::Neg => match t {
+                Type::Num => Ok(Type::Num),
+                Type::Any => Ok(Type::Any),
+                t => Err(Diagnostic::error(E_OPERATION, &format!("failed to negate: {}", t))),
+            },
+
+            // anything is truthy or not
+            Operand::Not => Ok(Type::Bool),
+
+            // tables have no type of their own, yet
+            Operand::Len => match t {
+                Type::Str | Type::Any | Type::Undefined => Ok(Type::Num),
+                t => Err(Diagnostic::error(E_OPERATION, &format!("failed to get length of: {}", t))),
+            },
+
+            ref op => Err(Diagnostic::error(E_OPERATION, &format!("'{}' isn't a unary operator", op))),
+        }
+    }
+
     pub fn operate(&self, lr: (Type, Type)) -> ParserResult<Type> {
         match *self {
             Operand::Pow => match lr {
@@ -468,7 +1184,7 @@ impl Operand {
                 (Type::Str, Type::Num) => Ok(Type::Str),
                 (Type::Str, Type::Any) => Ok(Type::Any),
                 (Type::Any, Type::Any) => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to pow: {:?} and {:?}", a, b))),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to pow: {} and {}", a, b))),
             },
 
             Operand::Mul => match lr {
@@ -478,7 +1194,7 @@ impl Operand {
                 (Type::Str, Type::Num)  => Ok(Type::Str),
                 (Type::Str, Type::Str)  => Ok(Type::Str),
                 (Type::Any, Type::Any)  => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to multiply: {:?} and {:?}", a, b))),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to multiply: {} and {}", a, b))),
             },
 
             Operand::Div => match lr {
@@ -486,7 +1202,7 @@ impl Operand {
                 (Type::Any, Type::Num)  => Ok(Type::Any),
                 (Type::Num, Type::Any)  => Ok(Type::Any),
                 (Type::Any, Type::Any)  => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to divide: {:?} and {:?}", a, b))),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to divide: {} and {}", a, b))),
             },
 
             Operand::Mod => match lr {
@@ -494,18 +1210,38 @@ impl Operand {
                 (Type::Any, Type::Num)  => Ok(Type::Any),
                 (Type::Num, Type::Any)  => Ok(Type::Any),
                 (Type::Any, Type::Any)  => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to mod: {:?} and {:?}", a, b))),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to mod: {} and {}", a, b))),
             },
 
             Operand::Add => match lr {
                 (Type::Num, Type::Num)  => Ok(Type::Num),
                 (Type::Any, Type::Num)  => Ok(Type::Any),
                 (Type::Num, Type::Any)  => Ok(Type::Any),
-                (Type::Str, Type::Num)  => Ok(Type::Str),
-                (Type::Str, Type::Str)  => Ok(Type::Str),
-                (Type::Str, Type::Bool) => Ok(Type::Str),
                 (Type::Any, Type::Any)  => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to add: {:?} and {:?}", a, b))),
+                (a, b) => {
+                    let d = Diagnostic::error(E_OPERATION, &format!("failed to add: {} and {}", a, b));
+
+                    // lua would try to add them as numbers
+                    if a == Type::Str || b == Type::Str {
+                        Err(d.with_suggestion("use '++' to concatenate strings"))
+                    } else {
+                        Err(d)
+                    }
+                },
+            },
+
+            Operand::Concat => match lr {
+                (Type::Str, Type::Str) => Ok(Type::Str),
+                (Type::Str, Type::Num) => Ok(Type::Str),
+                (Type::Num, Type::Str) => Ok(Type::Str),
+                (Type::Num, Type::Num) => Ok(Type::Str),
+                (Type::Any, Type::Str) => Ok(Type::Str),
+                (Type::Any, Type::Num) => Ok(Type::Str),
+                (Type::Str, Type::Any) => Ok(Type::Str),
+                (Type::Num, Type::Any) => Ok(Type::Str),
+                (Type::Any, Type::Any) => Ok(Type::Str),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to concatenate: {} and {}", a, b))
+                             .with_note("only strings and numbers concatenate, others can go through 'tostring'")),
             },
 
             Operand::Sub => match lr {
@@ -515,20 +1251,22 @@ impl Operand {
                 (Type::Str, Type::Num)  => Ok(Type::Str),
                 (Type::Str, Type::Str)  => Ok(Type::Str),
                 (Type::Any, Type::Any)  => Ok(Type::Any),
-                (a, b) => Err(ParserError::new(&format!("failed to subtract: {:?} and {:?}", a, b))),
+                (a, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to subtract: {} and {}", a, b))),
             },
 
             Operand::Equal | Operand::NEqual => Ok(Type::Bool),
 
             Operand::Lt | Operand::Gt | Operand::LtEqual | Operand::GtEqual => match lr {
-                (a @ Type::Bool, b @ _) => Err(ParserError::new(&format!("failed to '{:?} < {:?}'", a, b))),
-                (a @ _, b @ Type::Bool) => Err(ParserError::new(&format!("failed to '{:?} < {:?}'", a, b))),
-                (a @ Type::Str, b @ _)  => Err(ParserError::new(&format!("failed to '{:?} < {:?}'", a, b))),
-                (a @ _, b @ Type::Str)  => Err(ParserError::new(&format!("failed to '{:?} < {:?}'", a, b))),
+                (a @ Type::Bool, b) => Err(Diagnostic::error(E_OPERATION, &format!("failed to '{} < {}'", a, b))),
+                (a, b @ Type::Bool) => Err(Diagnostic::error(E_OPERATION, &format!("failed to '{} < {}'", a, b))),
+                (a @ Type::Str, b)  => Err(Diagnostic::error(E_OPERATION, &format!("failed to '{} < {}'", a, b))),
+                (a, b @ Type::Str)  => Err(Diagnostic::error(E_OPERATION, &format!("failed to '{} < {}'", a, b))),
                 _ => Ok(Type::Bool),
             },
 
             Operand::And | Operand::Or | Operand::Not => Ok(Type::Bool),
+
+            Operand::Neg | Operand::Len => Err(Diagnostic::error(E_OPERATION, &format!("'{}' takes a single operand", self))),
         }
     }
 
@@ -540,6 +1278,7 @@ impl Operand {
             Operand::Mod     => write!(f, "%"),
             Operand::Add     => write!(f, "+"),
             Operand::Sub     => write!(f, "-"),
+            Operand::Concat  => write!(f, ".."),
             Operand::Equal   => write!(f, "=="),
             Operand::NEqual  => write!(f, "~="),
             Operand::Lt      => write!(f, "<"),
@@ -549,6 +1288,8 @@ impl Operand {
             Operand::And     => write!(f, "and"),
             Operand::Or      => write!(f, "or"),
             Operand::Not     => write!(f, "not"),
+            Operand::Neg     => write!(f, "-"),
+            Operand::Len     => write!(f, "#"),
         }
     }
 }
@@ -559,23 +1300,139 @@ impl fmt::Display for Operand {
     }
 }
 
-pub fn get_operand(v: &str) -> Option<(Operand, u8)> {
+pub fn get_operand(v: &str) -> Option<Operand> {
     match v {
-        "^"   => Some((Operand::Pow, 0)),
-        "*"   => Some((Operand::Mul, 1)),
-        "/"   => Some((Operand::Div, 1)),
-        "%"   => Some((Operand::Mod, 1)),
-        "+"   => Some((Operand::Add, 2)),
-        "-"   => Some((Operand::Sub, 2)),
-        "=="  => Some((Operand::Equal, 3)),
-        "!="  => Some((Operand::NEqual, 3)),
-        "<"   => Some((Operand::Lt, 4)),
-        ">"   => Some((Operand::Gt, 4)),
-        "<="  => Some((Operand::LtEqual, 4)),
-        ">="  => Some((Operand::GtEqual, 4)),
-        "!"   => Some((Operand::Not, 4)),
-        "and" => Some((Operand::And, 4)),
-        "or"  => Some((Operand::Or, 4)),
+        "^"   => Some(Operand::Pow),
+        "*"   => Some(Operand::Mul),
+        "/"   => Some(Operand::Div),
+        "%"   => Some(Operand::Mod),
+        "+"   => Some(Operand::Add),
+        "-"   => Some(Operand::Sub),
+        "++"  => Some(Operand::Concat),
+        "=="  => Some(Operand::Equal),
+        "!="  => Some(Operand::NEqual),
+        "<"   => Some(Operand::Lt),
+        ">"   => Some(Operand::Gt),
+        "<="  => Some(Operand::LtEqual),
+        ">="  => Some(Operand::GtEqual),
+        "!"   => Some(Operand::Not),
+        "not" => Some(Operand::Not),
+        "#"   => Some(Operand::Len),
+        "and" => Some(Operand::And),
+        "or"  => Some(Operand::Or),
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::lexer::{Layout, Token, TokenPosition, string_value};
+    use super::super::{Parser, Traveler};
+
+    // what lua reads back from a literal's body, for the escapes `escape_string` writes
+    fn lua_value(body: &str) -> String {
+        let mut value = String::new();
+        let mut chars = body.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                value.push(c);
+                continue
+            }
+
+            match chars.next().unwrap() {
+                'n' => value.push('\n'),
+                'r' => value.push('\r'),
+                't' => value.push('\t'),
+                d if d.is_ascii_digit() => {
+                    let digits: String = Some(d).into_iter().chain((0 .. 2).map(|_| chars.next().unwrap())).collect();
+                    value.push(digits.parse::<u8>().unwrap() as char)
+                },
+                e => value.push(e),
+            }
+        }
+
+        value
+    }
+
+    fn lua(written: &str) -> String {
+        Expression::StringLiteral(Rc::new(string_value(written)), Span::default()).to_string()
+    }
+
+    #[test]
+    fn escapes() {
+        let cases = [
+            (r#""\\""#, r#""\\""#),
+            (r#""\"""#, r#""\"""#),
+            (r#"'\''"#, r#""'""#),
+            (r#""\{\}""#, r#""{}""#),
+            (r#""\n""#, r#""\n""#),
+            (r#""\r""#, r#""\r""#),
+            (r#""\t""#, r#""\t""#),
+            (r#"r"a\nb\""#, r#""a\\nb\\""#),
+            (r#"r'"'"#, r#""\"""#),
+            ("\"\"\"two\nlines\"\"\"", r#""two\nlines""#),
+        ];
+
+        for &(written, expected) in cases.iter() {
+            assert_eq!(lua(written), expected, "{}", written);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let written = [
+            r#""\\ \' \" \{ \} \n \r \t""#,
+            r#"r"C:\path\{x}\""#,
+            "\"\u{7}1 \u{0} é\"",
+            "'''it's \"quoted\"\n\\t'''",
+        ];
+
+        for w in written.iter() {
+            let value   = string_value(w);
+            let literal = lua(w);
+
+            assert_eq!(lua_value(&literal[1 .. literal.len() - 1]), value, "{}", w);
+        }
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(escape_string("\u{7}1"), "\\0071");
+    }
+
+    fn warnings(source: &str) -> Vec<Diagnostic> {
+        let tokens: Vec<Token> = Layout::new(source).collect();
+        let (statements, _) = Parser::new(Traveler::new(&tokens)).parse();
+
+        visit_statements(&statements, &Rc::new(SymTab::new_global()), &Rc::new(Env::new_global())).unwrap();
+
+        unused_names(&statements)
+    }
+
+    fn unused(source: &str) -> Vec<String> {
+        warnings(source).iter().map(|d| d.message.clone()).collect()
+    }
+
+    #[test]
+    fn unused_warnings() {
+        assert_eq!(unused("num (num a, num b) f =\n  x = 1\n  a"), ["unused parameter 'b'", "unused variable 'x'"]);
+        assert_eq!(unused("num (num _a) f =\n  _x = 1\n  2"), Vec::<String>::new());
+
+        // read by a nested function, and read by the right-hand of a reassignment
+        assert_eq!(unused("any (num a) f =\n  num (num b) g =\n    a + b\n  x = 1\n  x = x + 1\n  g 1"), Vec::<String>::new());
+
+        // globals may be read by whatever requires the file
+        assert_eq!(unused("x = 1"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unused_spans() {
+        let at = |line, col, len| Some(Span::new(TokenPosition::new(line, col), TokenPosition::new(line, col + len)));
+
+        let spans: Vec<_> = warnings("num (num a, num bb) f =\n  xs = 1\n  a").iter().map(|d| d.span()).collect();
+
+        assert_eq!(spans, [at(1, 16, 2), at(2, 2, 2)]);
+    }
+}
@@ -1,13 +1,13 @@
-pub mod error;
 pub mod ast;
 pub mod traveler;
 pub mod parser;
 
-pub use self::error::*;
 pub use self::ast::*;
 pub use self::traveler::*;
 pub use self::parser::*;
 
-pub type ParserResult<T> = Result<T, ParserError>;
+use super::diagnostic::Diagnostic;
+
+pub type ParserResult<T> = Result<T, Diagnostic>;
 
 pub use super::lexer;
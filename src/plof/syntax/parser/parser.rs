@@ -1,30 +1,64 @@
 use std::rc::Rc;
+use std::cell::Cell;
+use std::mem;
 
 use super::*;
-use super::ParserError;
+use super::super::diagnostic::*;
 
-use super::lexer::TokenType;
+use super::lexer::{Token, TokenType, Span, StringPart, Scanner, parse_number, string_value, split_interpolated};
 
-pub struct Parser {
-    traveler: Traveler,
+pub struct Parser<'a> {
+    traveler: Traveler<'a>,
+    errors:   Vec<Diagnostic>,
 }
 
-#[allow(dead_code)]
-impl Parser {
-    pub fn new(traveler: Traveler) -> Parser {
+impl<'a> Parser<'a> {
+    pub fn new(traveler: Traveler<'a>) -> Parser<'a> {
         Parser {
             traveler,
+            errors: Vec::new(),
         }
     }
 
-    pub fn parse(&mut self) -> ParserResult<Vec<Statement>> {
+    // parses every statement it can, skipping past broken ones; the statements
+    // come back along with every error met on the way, nested blocks included
+    pub fn parse(&mut self) -> (Vec<Statement>, Vec<Diagnostic>) {
+        let stack = self.statements();
+
+        (stack, mem::take(&mut self.errors))
+    }
+
+    // the statements up to the end of the block or source, leaving the Dedent or EOF current
+    fn statements(&mut self) -> Vec<Statement> {
         let mut stack = Vec::new();
-        while self.traveler.remaining() > 2 {
-            stack.push(try!(self.statement()));
+
+        loop {
+            match self.traveler.current().token_type {
+                TokenType::Dedent | TokenType::EOF => break,
+                TokenType::EOL => {
+                    self.traveler.next();
+                    continue
+                },
+                _ => (),
+            }
+
+            match self.statement() {
+                Ok(s)  => stack.push(s),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.traveler.synchronize();
+                },
+            }
+
             self.traveler.next();
         }
 
-        Ok(stack)
+        stack
+    }
+
+    fn unexpected(&self) -> Diagnostic {
+        let current = self.traveler.current();
+        Diagnostic::error(E_UNEXPECTED, &format!("unexpected {}", current)).with_span(current.span)
     }
 
     pub fn skip_white(&mut self) -> ParserResult<()> {
@@ -41,43 +75,64 @@ impl Parser {
                 self.traveler.next();
                 self.statement()
             },
-            TokenType::Keyword => match self.traveler.current_content().as_str() {
+            TokenType::Keyword => match self.traveler.current_content() {
                 "return" => {
+                    let span = self.traveler.current().span;
+
                     self.traveler.next();
 
                     if self.traveler.current_content() == "\n" {
-                        Ok(Statement::Return(None))
+                        Ok(Statement::Return(None, span))
                     } else {
-                        Ok(Statement::Return(Some(Rc::new(try!(self.expression())))))
+                        Ok(Statement::Return(Some(Rc::new(self.expression()?)), span))
                     }
-                }
-                _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                },
+                "if"     => self.if_statement(),
+                "unless" => self.unless_statement(),
+                "while"  => self.while_statement(),
+                "for"    => self.for_statement(),
+                _ => Err(self.unexpected()),
             },
-            _ => Ok(Statement::Expression(Rc::new(try!(self.expression())))),
+            _ => Ok(Statement::Expression(Rc::new(self.expression()?))),
         }
     }
 
     pub fn term(&mut self) -> ParserResult<Expression> {
-        match self.traveler.current().token_type {
-            TokenType::EOL => {
-                self.traveler.next();
-                match self.traveler.current().token_type {
-                    TokenType::Block(_) => return Ok(Expression::Block(Rc::new(try!(self.block())))),
-                    TokenType::EOL      => return Ok(Expression::EOF),
-                    _ => (),
-                }
-            },
-            _ => (),
+        // an expression can go on in an indented block, but never on the next line, or
+        // past the end of the block or source, which are what's missing it
+        if self.traveler.current().token_type == TokenType::EOL {
+            self.traveler.next();
+
+            if self.traveler.current().token_type == TokenType::Indent {
+                let indent = self.traveler.current().span;
+                let body   = self.block()?;
+
+                let span = match (body.first(), body.last()) {
+                    (Some(first), Some(last)) => first.span().to(last.span()),
+                    _                         => indent,
+                };
+
+                return Ok(Expression::Block(Rc::new(body), span))
+            }
+
+            self.traveler.prev();
+            return Err(self.traveler.expected("expression"))
         }
 
+        let span = self.traveler.current().span;
+
         match self.traveler.current().token_type {
-            TokenType::IntLiteral    => Ok(Expression::NumberLiteral(self.traveler.current_content().parse::<f64>().unwrap())),
-            TokenType::FloatLiteral  => Ok(Expression::NumberLiteral(self.traveler.current_content().parse::<f64>().unwrap())),
-            TokenType::BoolLiteral   => Ok(Expression::BoolLiteral(self.traveler.current_content() == "true")),
-            TokenType::StringLiteral => Ok(Expression::StringLiteral(Rc::new(self.traveler.current_content().clone()))),
+            TokenType::IntLiteral |
+            TokenType::FloatLiteral  => match parse_number(self.traveler.current_content()) {
+                Ok(n)    => Ok(Expression::NumberLiteral(n, span)),
+                Err(why) => Err(Diagnostic::error(E_NUMBER, "malformed number literal").with_label(span, &why)),
+            },
+            TokenType::BoolLiteral   => Ok(Expression::BoolLiteral(self.traveler.current_content() == "true", span)),
+            TokenType::StringLiteral => Ok(Expression::StringLiteral(Rc::new(string_value(self.traveler.current_content())), span)),
+            TokenType::InterpolatedLiteral => self.interpolated(span),
             TokenType::Identifier    => {
-                let id = Expression::Identifier(Rc::new(self.traveler.current_content()));
-                let name = Rc::new(self.traveler.current_content());
+                let id = Expression::Identifier(Rc::new(self.traveler.current_content().to_owned()), span);
+                let name = Rc::new(self.traveler.current_content().to_owned());
 
                 self.traveler.next();
 
@@ -86,39 +141,54 @@ impl Parser {
                     TokenType::FloatLiteral |
                     TokenType::BoolLiteral |
                     TokenType::StringLiteral |
+                    TokenType::InterpolatedLiteral |
                     TokenType::Identifier |
                     TokenType::Symbol => {
                         if self.traveler.current().token_type == TokenType::Symbol {
-                            match self.traveler.current_content().as_str() {
-                                "(" | ")" => (),
-                                "!"       => return Ok(Expression::Call(Rc::new(id), Rc::new(vec!()))),
+                            match self.traveler.current_content() {
+                                "(" => (),
+                                ")" | "," | ".." | "]" => {
+                                    self.traveler.prev();
+                                    return Ok(id)
+                                },
+                                // 'f!' calls f without arguments, 'f !x' passes '!x' along
+                                "!" if !self.spaced_before() => return Ok(Expression::Call(Rc::new(id), Rc::new(vec!()))),
+                                "!"       => (),
                                 "="       => {
                                     self.traveler.next();
                                     let expr = self.expression()?;
-                                    self.traveler.next();
 
-                                    return Ok(Expression::Definition(None, name, Rc::new(expr)))
+                                    return Ok(Expression::Definition {
+                                        t:     None,
+                                        name,
+                                        right: Rc::new(expr),
+                                        local: Cell::new(true),
+                                        span,
+                                    })
                                 },
                                 ":"       => {
                                     self.traveler.next();
                                     let expr = self.expression()?;
-                                    println!("found in key: {}", self.traveler.current_content());
-
-                                    self.traveler.next();
 
-                                    return Ok(Expression::Key(None, name, Rc::new(expr)))
+                                    return Ok(Expression::Key {
+                                        t:     None,
+                                        name,
+                                        right: Rc::new(expr),
+                                        span,
+                                    })
                                 }
-                                _   => return Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                                _   => return Err(self.unexpected()),
                             }
                         }
 
-                        let call = try!(self.call(id));
-
-                        self.traveler.next();
-
-                        return Ok(call)
+                        return self.call(id)
                     },
 
+                    // 'f not x', 'f #x' and 'f -x' pass their argument along, unlike 'f - x'
+                    TokenType::Operator if self.traveler.current_content() == "not" => return self.call(id),
+                    TokenType::Operator if self.traveler.current_content() == "#" => return self.call(id),
+                    TokenType::Operator if self.traveler.current_content() == "-" && self.prefix_minus() => return self.call(id),
+
                     _ => (),
                 }
 
@@ -127,7 +197,7 @@ impl Parser {
                 Ok(id)
             },
 
-            TokenType::Symbol => match self.traveler.current_content().as_str() {
+            TokenType::Symbol => match self.traveler.current_content() {
                 "[" => {
                     self.traveler.next();
                     
@@ -144,9 +214,7 @@ impl Parser {
                         self.skip_white()?;
                     }
                     
-                    self.traveler.next(); // "]"
-                    
-                    Ok(Expression::DictLiteral(Rc::new(body)))
+                    Ok(Expression::DictLiteral(Rc::new(body), span.to(self.traveler.current().span)))
                 },
 
                 "(" => {
@@ -163,21 +231,11 @@ impl Parser {
                         TokenType::FloatLiteral |
                         TokenType::BoolLiteral |
                         TokenType::StringLiteral |
-                        TokenType::Identifier |
-                        TokenType::Symbol => {
-                            if self.traveler.current().token_type == TokenType::Symbol {
-                                match self.traveler.current_content().as_str() {
-                                    "(" => (),
-                                    _   => return Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected here: {}", self.traveler.current_content()))),
-                                }
-                            }
-
-                            let call = try!(self.call(expr));
+                        TokenType::InterpolatedLiteral |
+                        TokenType::Identifier => return self.call(expr),
 
-                            self.traveler.next();
-
-                            return Ok(call)
-                        },
+                        // anything else, like ',' or ']', ends the expression
+                        TokenType::Symbol if self.traveler.current_content() == "(" => return self.call(expr),
 
                         _ => (),
                     }
@@ -187,22 +245,18 @@ impl Parser {
                     Ok(expr)
                 },
 
-                "," | ")" => { // bad hack here
-                    self.traveler.next();
-                    return self.term()
-                },
-
-                _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected symbol: {}", self.traveler.current_content()))),
+                _ => Err(self.unexpected()),
             },
 
             TokenType::Type => {
-                let retty = get_type(&self.traveler.current_content()).unwrap();
+                let retty = get_type(self.traveler.current_content()).unwrap();
 
                 self.traveler.next();
 
                 match self.traveler.current().token_type {
                     TokenType::Identifier => {
-                        let id = self.traveler.current_content();
+                        let id   = self.traveler.current_content();
+                        let span = span.to(self.traveler.current().span);
 
                         self.traveler.next();
 
@@ -210,45 +264,55 @@ impl Parser {
                             self.traveler.next();
                             
                             let expr = self.expression()?;
-                            self.traveler.next();
 
-                            Ok(Expression::Definition(Some(retty), Rc::new(id), Rc::new(expr)))
+                            Ok(Expression::Definition {
+                                t:     Some(retty),
+                                name:  Rc::new(id.to_owned()),
+                                right: Rc::new(expr),
+                                local: Cell::new(true),
+                                span,
+                            })
                         } else if self.traveler.current_content() == ":" {
                             self.traveler.next();
                             
                             let expr = self.expression()?;
-                            self.traveler.next();
 
-                            Ok(Expression::Key(Some(retty), Rc::new(id), Rc::new(expr)))
+                            Ok(Expression::Key {
+                                t:     Some(retty),
+                                name:  Rc::new(id.to_owned()),
+                                right: Rc::new(expr),
+                                span,
+                            })
                         } else {
-                            Err(ParserError::new_pos(self.traveler.current().position, &format!("expected '=' or ':', found: {}", self.traveler.current_content())))
+                            Err(self.traveler.expected("'=' or ':'"))
                         }
                     },
 
-                    TokenType::Symbol => match self.traveler.current_content().as_str() {
+                    TokenType::Symbol => match self.traveler.current_content() {
                         "(" => {
                             self.traveler.next();
 
                             let mut param_names = Vec::new();
                             let mut param_types = Vec::new();
+                            let mut param_spans = Vec::new();
                             
                             while self.traveler.current_content() != ")" {
                                 let mut t: Type = Type::Any;
                                 
                                 match self.traveler.current().token_type {
                                     TokenType::Type => {
-                                        t = get_type(&self.traveler.current_content()).unwrap();
+                                        t = get_type(self.traveler.current_content()).unwrap();
                                         self.traveler.next();
                                     },
 
                                     TokenType::Identifier => (),
                                     
-                                    TokenType::Symbol => match self.traveler.current_content().as_str() {
+                                    TokenType::Symbol => match self.traveler.current_content() {
                                         "," | "..." => (),
-                                        _ => return Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                                        _ => return Err(self.unexpected()),
                                     },
 
-                                    _ => return Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                                    _ => return Err(self.unexpected()),
                                 }
 
                                 if self.traveler.current_content() == "," {
@@ -258,10 +322,12 @@ impl Parser {
                                         t = Type::Many(Rc::new(t));
                                         self.traveler.next();
                                     } else {
-                                        let id = Rc::new(try!(self.traveler.expect(TokenType::Identifier)));
+                                        let at = self.traveler.current().span;
+                                        let id = Rc::new(self.traveler.expect(TokenType::Identifier)?.to_owned());
                                         self.traveler.next();
 
                                         param_names.push(id);
+                                        param_spans.push(at);
                                     }
                                     
                                     param_types.push(t);
@@ -273,145 +339,423 @@ impl Parser {
                             let mut name = None;
 
                             if self.traveler.current().token_type == TokenType::Identifier {
-                                name = Some(Rc::new(self.traveler.current_content()));
+                                name = Some(Rc::new(self.traveler.current_content().to_owned()));
                                 self.traveler.next();
                             }
 
-                            try!(self.traveler.expect_content("="));
+                            self.traveler.expect_content("=")?;
 
-                            self.traveler.next();
+                            let span = span.to(self.traveler.current().span);
 
-                            let body: Rc<Vec<Statement>>;
+                            self.traveler.next();
 
-                            match self.traveler.current_content().as_str() {
+                            let body = match self.traveler.current_content() {
                                 "\n" => {
                                     self.traveler.next();
-                                    body = Rc::new(try!(self.block()));
+                                    Rc::new(self.block()?)
                                 },
-                                _ => body = Rc::new(vec![try!(self.statement())]),
-                            }
+                                _ => Rc::new(vec![self.statement()?]),
+                            };
 
                             Ok(Expression::Lambda {
                                 name,
                                 retty,
                                 param_names,
                                 param_types,
+                                param_spans,
                                 body,
+                                span,
                             })
                         },
 
-                        _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                        _ => Err(self.unexpected()),
                     },
 
-                    _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {}", self.traveler.current_content()))),
+                    _ => Err(self.unexpected()),
                 }
             },
 
-            _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("unexpected: {:#?}", self.traveler.current()))),
+            _ => Err(self.unexpected()),
         }
     }
 
-    fn block(&mut self) -> ParserResult<Vec<Statement>> {
-        match self.traveler.current().token_type {
-            TokenType::Block(ref v) => {
-                let mut p = Parser::new(Traveler::new(v.clone()));
-                Ok(try!(p.parse()))
-            },
-            _ => Err(ParserError::new_pos(self.traveler.current().position, &format!("expected block, found: {}", self.traveler.current_content()))),
-        }
-    }
+    fn if_statement(&mut self) -> ParserResult<Statement> {
+        self.traveler.next();
 
-    fn expression(&mut self) -> ParserResult<Expression> {
-        if self.traveler.current_content() == "\n" {
+        let condition = Rc::new(self.expression()?);
+        let body      = Rc::new(self.body()?);
+
+        let mut elifs  = Vec::new();
+        let mut orelse = None;
+
+        loop {
             self.traveler.next();
+
+            match self.traveler.current_content() {
+                "elif" => {
+                    self.traveler.next();
+
+                    let condition = Rc::new(self.expression()?);
+                    elifs.push((condition, Rc::new(self.body()?)))
+                },
+                "else" => {
+                    orelse = Some(Rc::new(self.body()?));
+                    break
+                },
+                _ => {
+                    self.traveler.prev();
+                    break
+                },
+            }
         }
 
-        let expr = try!(self.term());
+        Ok(Statement::If {
+            condition,
+            body,
+            elifs,
+            orelse,
+        })
+    }
+
+    fn unless_statement(&mut self) -> ParserResult<Statement> {
+        self.traveler.next();
+
+        let condition = Rc::new(self.expression()?);
+        let body      = Rc::new(self.body()?);
 
         self.traveler.next();
-        if self.traveler.remaining() > 0 {
-            if self.traveler.current().token_type == TokenType::Operator {
-                return self.operation(expr)
-            }
+
+        let orelse = if self.traveler.current_content() == "else" {
+            Some(Rc::new(self.body()?))
+        } else {
             self.traveler.prev();
-        }
-        Ok(expr)
+            None
+        };
+
+        Ok(Statement::Unless {
+            condition,
+            body,
+            orelse,
+        })
     }
 
-    fn call(&mut self, caller: Expression) -> ParserResult<Expression> {
-        let mut args = Vec::new();
+    fn while_statement(&mut self) -> ParserResult<Statement> {
+        self.traveler.next();
+
+        let condition = Rc::new(self.expression()?);
+        let body      = Rc::new(self.body()?);
+
+        Ok(Statement::While {
+            condition,
+            body,
+        })
+    }
+
+    fn for_statement(&mut self) -> ParserResult<Statement> {
+        self.traveler.next();
+
+        let mut names      = Vec::new();
+        let mut names_span = self.traveler.current().span;
+
+        loop {
+            names.push(Rc::new(self.traveler.expect(TokenType::Identifier)?.to_owned()));
+            names_span = names_span.to(self.traveler.current().span);
 
-        while self.traveler.current_content() != ")" && self.traveler.current_content() != "\n" {
-            args.push(try!(self.expression()));
             self.traveler.next();
 
             if self.traveler.current_content() == "," {
                 self.traveler.next();
+            } else {
+                break
             }
         }
 
-        Ok(Expression::Call(Rc::new(caller), Rc::new(args)))
-    }
-
-    fn operation(&mut self, expression: Expression) -> ParserResult<Expression> {
+        self.traveler.expect_content("in")?;
+        self.traveler.next();
 
-        let mut ex_stack = vec![expression];
-        let mut op_stack: Vec<(Operand, u8)> = Vec::new();
+        let expr = self.expression()?;
 
-        op_stack.push(get_operand(&self.traveler.current_content()).unwrap());
         self.traveler.next();
 
-        if self.traveler.current_content() == "\n" {
+        let iterator = if self.traveler.current_content() == ".." {
+            if names.len() != 1 {
+                return Err(Diagnostic::error(E_LOOP_NAMES, "expected a single name to range over")
+                           .with_span(names_span)
+                           .with_note("ranges count a single number, e.g. 'for i in 1..10'"))
+            }
+
             self.traveler.next();
+
+            Expression::Range(Rc::new(expr), Rc::new(self.expression()?))
+        } else {
+            if names.len() > 2 {
+                return Err(Diagnostic::error(E_LOOP_NAMES, "expected a key and value name to iterate with")
+                           .with_span(names_span)
+                           .with_note("tables are iterated by key, or key and value, e.g. 'for k, v in t'"))
+            }
+
+            self.traveler.prev();
+
+            expr
+        };
+
+        let body = Rc::new(self.body()?);
+
+        Ok(Statement::For {
+            names,
+            iterator: Rc::new(iterator),
+            body,
+        })
+    }
+
+    // an indented body following the current token, e.g. the block after `if a`
+    fn body(&mut self) -> ParserResult<Vec<Statement>> {
+        self.traveler.next();
+        self.traveler.expect(TokenType::EOL)?;
+        self.traveler.next();
+
+        self.block()
+    }
+
+    fn block(&mut self) -> ParserResult<Vec<Statement>> {
+        match self.traveler.current().token_type {
+            TokenType::Indent => {
+                self.traveler.next();
+                Ok(self.statements())
+            },
+            _ => Err(Diagnostic::error(E_EXPECTED_BLOCK, &format!("expected indented block, found {}", self.traveler.current()))
+                         .with_span(self.traveler.current().span)),
         }
+    }
+
+    fn expression(&mut self) -> ParserResult<Expression> {
+        self.binary(0)
+    }
 
-        ex_stack.push(try!(self.term()));
+    // precedence climbing: an operand, followed by every operator binding tighter than `min`,
+    // each taking a right-hand binding tighter still, or as tight for right-associative ones
+    fn binary(&mut self, min: u8) -> ParserResult<Expression> {
+        let mut left = self.unary()?;
 
-        let mut done = false;
-        while ex_stack.len() > 1 {
-            if !done && self.traveler.next() {
-                if self.traveler.current().token_type != TokenType::Operator {
+        loop {
+            if !self.traveler.next() {
+                break
+            }
+
+            let op = match self.infix_operator() {
+                Some(op) => op,
+                None     => {
                     self.traveler.prev();
-                    done = true;
-                    continue
-                }
+                    break
+                },
+            };
 
-                let (op, precedence) = get_operand(&self.traveler.current_content()).unwrap();
+            let (precedence, associativity) = op.precedence();
 
-                if precedence >= op_stack.last().unwrap().1 {
-                    let left  = ex_stack.pop().unwrap();
-                    let right = ex_stack.pop().unwrap();
+            if precedence <= min {
+                self.traveler.prev();
+                break
+            }
 
-                    ex_stack.push(Expression::Operation {
-                        right: Rc::new(left),
-                        op:    op_stack.pop().unwrap().0,
-                        left:  Rc::new(right)
-                    });
+            self.traveler.next();
 
-                    self.traveler.next();
+            let right = match associativity {
+                Associativity::Left  => self.binary(precedence)?,
+                Associativity::Right => self.binary(precedence - 1)?,
+            };
 
-                    ex_stack.push(try!(self.term()));
-                    op_stack.push((op, precedence));
+            left = Expression::Operation {
+                left:  Rc::new(left),
+                op,
+                right: Rc::new(right),
+            }
+        }
 
-                    continue
-                }
+        Ok(left)
+    }
+
+    // prefix operators, binding tighter than anything but '^', so '-a ^ 2' is '-(a ^ 2)'
+    fn unary(&mut self) -> ParserResult<Expression> {
+        let span = self.traveler.current().span;
+
+        let op = match self.traveler.current().token_type {
+            TokenType::Operator | TokenType::Symbol => match self.traveler.current_content() {
+                "-"         => Some(Operand::Neg),
+                "not" | "!" => Some(Operand::Not),
+                "#"         => Some(Operand::Len),
+                _           => None,
+            },
+            _ => None,
+        };
 
+        match op {
+            Some(op) => {
                 self.traveler.next();
 
-                ex_stack.push(try!(self.term()));
-                op_stack.push((op, precedence));
+                let expr = self.binary(op.precedence().0)?;
+
+                // negative literals stay literals
+                if let (Operand::Neg, Expression::NumberLiteral(n, end)) = (&op, &expr) {
+                    return Ok(Expression::NumberLiteral(-n, span.to(*end)))
+                }
+
+                Ok(Expression::Unary {
+                    op,
+                    expr: Rc::new(expr),
+                    span,
+                })
+            },
+
+            None => self.term(),
+        }
+    }
+
+    fn spaced_before(&self) -> bool {
+        match self.traveler.peek(-1) {
+            Some(before) => before.span.end != self.traveler.current().span.start,
+            None         => true,
+        }
+    }
+
+    // a '-' spaced from what's before it, but touching what's after
+    fn prefix_minus(&self) -> bool {
+        match self.traveler.peek(1) {
+            Some(after) => self.spaced_before() && after.span.start == self.traveler.current().span.end,
+            None        => false,
+        }
+    }
+
+    fn infix_operator(&self) -> Option<Operand> {
+        if self.traveler.current().token_type != TokenType::Operator {
+            return None
+        }
+
+        match get_operand(self.traveler.current_content()) {
+            Some(Operand::Not) | Some(Operand::Len) => None,
+            op => op,
+        }
+    }
+
+    // "hi {name}" into its text and expressions, each expression lexed and parsed on its own
+    fn interpolated(&mut self, span: Span) -> ParserResult<Expression> {
+        let written = self.traveler.current_content();
+
+        let parts = split_interpolated(written, span.start).map_err(|(why, at)| {
+            Diagnostic::error(E_INTERPOLATION, "malformed string interpolation").with_label(at, &why)
+        })?;
+
+        let mut exprs = Vec::new();
+
+        for part in parts {
+            match part {
+                StringPart::Text(text, span) => exprs.push((Expression::StringLiteral(Rc::new(text), span), Cell::new(true))),
+                StringPart::Code(code, span) => {
+                    let mut tokens: Vec<Token> = Scanner::new(&code, span.start).filter(|t| t.token_type != TokenType::Comment).collect();
+                    tokens.push(Token::new(TokenType::EOL, Span::new(span.end, span.end), "\n"));
+
+                    if let Some(e) = lexical_errors(&tokens).into_iter().next() {
+                        return Err(e)
+                    }
+
+                    let mut p = Parser::new(Traveler::new(&tokens));
+                    let expr  = p.expression();
+
+                    self.errors.append(&mut p.errors);
+
+                    let expr = expr?;
+                    p.traveler.next();
+
+                    if p.traveler.current().token_type != TokenType::EOL {
+                        return Err(p.unexpected())
+                    }
+
+                    exprs.push((expr, Cell::new(false)))
+                },
+            }
+        }
+
+        Ok(Expression::Interpolated {
+            parts: Rc::new(exprs),
+            span,
+        })
+    }
+
+    fn call(&mut self, caller: Expression) -> ParserResult<Expression> {
+        let mut args = Vec::new();
+
+        while self.traveler.current_content() != ")" && self.traveler.current_content() != "\n" {
+            args.push(self.expression()?);
+            self.traveler.next();
+
+            if self.traveler.current_content() == "," {
+                self.traveler.next();
             }
+        }
 
-            let left  = ex_stack.pop().unwrap();
-            let right = ex_stack.pop().unwrap();
+        self.traveler.prev(); // leaves the terminator to the caller
 
-            ex_stack.push(Expression::Operation {
-                right: Rc::new(left),
-                op:    op_stack.pop().unwrap().0,
-                left:  Rc::new(right)
-            });
+        Ok(Expression::Call(Rc::new(caller), Rc::new(args)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::lexer::Layout;
+
+    // each statement as the lua it becomes, which brackets every operation it holds
+    fn parse(source: &str) -> Result<Vec<String>, Vec<&'static str>> {
+        let tokens: Vec<Token> = Layout::new(source).collect();
+        let (statements, errors) = Parser::new(Traveler::new(&tokens)).parse();
+
+        if errors.is_empty() {
+            Ok(statements.iter().map(|s| s.to_string()).collect())
+        } else {
+            Err(errors.iter().map(|e| e.code).collect())
         }
+    }
+
+    fn shape(source: &str) -> String {
+        parse(source).unwrap().concat()
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(shape("a = 1 + 2 * 3"), "local a = ((1)+(((2)*(3))));");
+        assert_eq!(shape("a = 1 * 2 + 3"), "local a = ((((1)*(2)))+(3));");
+        assert_eq!(shape("a = b == 1 and c or d"), "local a = ((((((b)==(1)))and(c)))or(d));");
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(shape("a = b - c - d"), "local a = ((((b)-(c)))-(d));");
+        assert_eq!(shape("a = b ^ c ^ d"), "local a = ((b)^(((c)^(d))));");
+    }
+
+    #[test]
+    fn unary() {
+        assert_eq!(shape("a = -b ^ 2"), "local a = (- (((b)^(2))));");
+        assert_eq!(shape("a = not b and c"), "local a = (((not (b)))and(c));");
+        assert_eq!(shape("a = !b"), "local a = (not (b));");
+        assert_eq!(shape("a = - - b"), "local a = (- ((- (b))));");
+    }
+
+    #[test]
+    fn parentheses() {
+        assert_eq!(shape("a = (1 + 2) * 3"), "local a = ((((1)+(2)))*(3));");
+        assert_eq!(shape("a = b - (c - d)"), "local a = ((b)-(((c)-(d))));");
+    }
+
+    #[test]
+    fn missing_operand() {
+        assert_eq!(parse("a = 1 +"), Err(vec![E_EXPECTED]));
+        assert_eq!(parse("a = 1 +\nprint 2"), Err(vec![E_EXPECTED]));
+        assert_eq!(parse("a =\nprint 1"), Err(vec![E_EXPECTED]));
+    }
 
-        Ok(ex_stack.pop().unwrap())
+    #[test]
+    fn stray_symbol() {
+        assert_eq!(parse("a = )"), Err(vec![E_UNEXPECTED]));
+        assert_eq!(parse("a = ,"), Err(vec![E_UNEXPECTED]));
     }
 }
@@ -1,21 +1,24 @@
 use super::lexer::{Token, TokenType};
-use super::{ParserError, ParserResult};
+use super::ParserResult;
+use super::super::diagnostic::*;
 
+// walks the tokens of a `Layout`, in which blocks are between an Indent and its Dedent
 #[derive(Debug, Clone)]
-pub struct Traveler {
-    pub tokens: Vec<Token>,
+pub struct Traveler<'a> {
+    pub tokens: &'a [Token<'a>],
     top: usize,
 }
 
-#[allow(dead_code)]
-impl Traveler {
-    pub fn new(tokens: Vec<Token>) -> Traveler {
+impl<'a> Traveler<'a> {
+    pub fn new(tokens: &'a [Token<'a>]) -> Traveler<'a> {
         Traveler {
             tokens,
             top: 0,
         }
     }
 
+    // moves on a token, telling whether there was one; not an `Iterator`, it goes back too
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> bool {
         if self.top < self.tokens.len() {
             self.top += 1;
@@ -32,59 +35,110 @@ impl Traveler {
         false
     }
 
+    // skips the rest of a broken statement, stopping at the end of its line, or past
+    // the indented block and any 'elif'/'else' branches that belong to it; the end of
+    // the block it's in is left to whatever is parsing that
+    pub fn synchronize(&mut self) {
+        let mut depth = 0;
+
+        while self.top < self.tokens.len() {
+            match self.current().token_type {
+                TokenType::Indent => depth += 1,
+
+                TokenType::Dedent if depth == 0 => {
+                    self.top -= 1;
+                    return
+                },
+
+                TokenType::Dedent => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        match self.tokens.get(self.top + 1) {
+                            Some(t) if t.token_type == TokenType::Keyword && (t.content() == "elif" || t.content() == "else") => (),
+                            _ => return,
+                        }
+                    }
+                },
+
+                TokenType::EOL if depth == 0 => match self.tokens.get(self.top + 1).map(|t| &t.token_type) {
+                    Some(&TokenType::Indent) => (),
+                    _                        => return,
+                },
+
+                TokenType::EOF => return,
+
+                _ => (),
+            }
+
+            self.top += 1
+        }
+    }
+
     pub fn remaining(&self) -> usize {
         self.tokens.len() - self.top + 1
     }
 
-    pub fn current(&self) -> &Token {
+    pub fn current(&self) -> &'a Token<'a> {
         if self.top > self.tokens.len() - 1 {
             return &self.tokens[self.tokens.len() - 1];
         }
         &self.tokens[self.top]
     }
 
-    pub fn get(&self, i: usize) -> &Token {
+    pub fn get(&self, i: usize) -> &'a Token<'a> {
         assert!(i > 0 && i < self.tokens.len(), "trying to get non-existing token!");
         &self.tokens[i]
     }
 
-    pub fn current_content(&self) -> String {
-        self.current().content().clone()
+    // the token `offset` away from the current one, if any
+    pub fn peek(&self, offset: isize) -> Option<&'a Token<'a>> {
+        let i = self.top as isize + offset;
+
+        if i < 0 {
+            None
+        } else {
+            self.tokens.get(i as usize)
+        }
     }
 
-    pub fn expect(&self, token: TokenType) -> ParserResult<String> {
+    pub fn current_content(&self) -> &'a str {
+        self.current().content()
+    }
+
+    pub fn expect(&self, token: TokenType) -> ParserResult<&'a str> {
         if self.current().token_type == token {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new(&format!("expected '{:?}', found '{}'", token, self.current_content())))
+            Err(self.expected(&format!("{}", token)))
         }
     }
 
-    pub fn expect_content(&self, content: &str) -> ParserResult<String> {
-        if &self.current_content() == content {
+    pub fn expect_content(&self, content: &str) -> ParserResult<&'a str> {
+        if self.current_content() == content {
             Ok(self.current_content())
         } else {
-            Err(ParserError::new(&format!("expected '{}', found '{}'", content, self.current_content())))
+            Err(self.expected(&format!("'{}'", content)))
         }
     }
 
-    pub fn expect_contents(&self, sequence: Vec<String>) -> Result<Vec<&Token>, String> {
-        let mut accum: usize = 0;
+    pub fn expected(&self, what: &str) -> Diagnostic {
+        Diagnostic::error(E_EXPECTED, &format!("expected {}, found {}", what, self.current())).with_span(self.current().span)
+    }
 
+    pub fn expect_contents(&self, sequence: Vec<String>) -> ParserResult<Vec<&'a Token<'a>>> {
         let mut res = Vec::new();
 
-        for c in sequence {
+        for (accum, c) in sequence.into_iter().enumerate() {
             if self.top + accum >= self.tokens.len() {
-                return Err(format!("expected '{}', found end of source >:(", c))
+                return Err(Diagnostic::error(E_EXPECTED, &format!("expected '{}', found end of source >:(", c)).with_span(self.current().span))
             }
 
-            if &c != self.tokens[self.top + accum].content() {
-                return Err(format!("expected '{}', found '{}'", c, self.tokens[self.top + accum].content()))
+            if c != self.tokens[self.top + accum].content() {
+                return Err(Diagnostic::error(E_EXPECTED, &format!("expected '{}', found {}", c, self.tokens[self.top + accum])).with_span(self.tokens[self.top + accum].span))
             }
 
             res.push(self.get(self.top + accum));
-
-            accum += 1
         }
 
         Ok(res)
@@ -0,0 +1,161 @@
+use super::diagnostic::{Diagnostic, Label, Severity};
+
+const RESET:  &str = "\x1b[0m";
+const BOLD:   &str = "\x1b[1m";
+const RED:    &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN:  &str = "\x1b[1;32m";
+const BLUE:   &str = "\x1b[1;34m";
+
+// renders diagnostics against the original source, rustc style:
+//
+// error[E0300]: use of undeclared: prnt
+//  --> samples/hello.plof:1:1
+//   |
+// 1 | prnt "hello"
+//   | ^^^^ not found in this scope
+//   |
+//   = help: did you mean 'print'?
+pub struct Renderer<'a> {
+    name:  &'a str,
+    lines: Vec<&'a str>,
+    color: bool,
+}
+
+impl<'a> Renderer<'a> {
+    pub fn new(name: &'a str, source: &'a str, color: bool) -> Renderer<'a> {
+        Renderer {
+            name,
+            lines: source.lines().collect(),
+            color,
+        }
+    }
+
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn severity_style(severity: Severity) -> &'static str {
+        match severity {
+            Severity::Error   => RED,
+            Severity::Warning => YELLOW,
+            Severity::Note    => GREEN,
+        }
+    }
+
+    // spans are 1-based lines, 0-based columns; anything outside the source has no snippet
+    fn line(&self, label: &Label) -> Option<&'a str> {
+        let line = label.span.start.line;
+
+        if line == 0 {
+            None
+        } else {
+            self.lines.get(line - 1).cloned()
+        }
+    }
+
+    // the marker line under a source line, keeping tabs so carets stay aligned
+    fn marker(&self, source: &str, label: &Label, mark: char, style: &str) -> String {
+        let span = label.span;
+
+        let start = span.start.col.min(source.chars().count());
+        let width = if span.end.line == span.start.line && span.end.col > span.start.col {
+            span.end.col - span.start.col
+        } else if span.end.line > span.start.line {
+            source.chars().count().saturating_sub(start).max(1)
+        } else {
+            1
+        };
+
+        let padding: String = source.chars().take(start).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+        let marks: String   = ::std::iter::repeat_n(mark, width).collect();
+
+        let mut text = marks;
+
+        if !label.message.is_empty() {
+            text.push(' ');
+            text.push_str(&label.message)
+        }
+
+        format!("{}{}", padding, self.paint(style, &text))
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let style = Renderer::severity_style(diagnostic.severity);
+
+        let mut out = format!(
+            "{}{}",
+            self.paint(style, &format!("{}[{}]", diagnostic.severity, diagnostic.code)),
+            self.paint(BOLD, &format!(": {}", diagnostic.message)),
+        );
+
+        // labels in source order, so snippets read top to bottom
+        let mut labels: Vec<(&Label, char, &str)> = Vec::new();
+
+        if let Some(ref primary) = diagnostic.primary {
+            labels.push((primary, '^', style))
+        }
+
+        for label in diagnostic.secondary.iter() {
+            labels.push((label, '-', BLUE))
+        }
+
+        labels.sort_by_key(|&(l, _, _)| (l.span.start.line, l.span.start.col));
+
+        let gutter_width = labels.iter().map(|&(l, _, _)| format!("{}", l.span.start.line).len()).max().unwrap_or(1);
+        let gutter       = " ".repeat(gutter_width);
+        let bar          = self.paint(BLUE, "|");
+
+        match diagnostic.primary {
+            Some(ref primary) => out.push_str(&format!(
+                "\n{}{} {}:{}:{}", gutter, self.paint(BLUE, "-->"), self.name, primary.span.start.line, primary.span.start.col + 1
+            )),
+            None => out.push_str(&format!("\n{}{} {}", gutter, self.paint(BLUE, "-->"), self.name)),
+        }
+
+        let mut last_line = None;
+        let mut any       = false;
+
+        for &(label, mark, mark_style) in labels.iter() {
+            let source = match self.line(label) {
+                Some(s) => s,
+                None    => continue,
+            };
+
+            if !any {
+                out.push_str(&format!("\n{} {}", gutter, bar));
+                any = true
+            }
+
+            let line = label.span.start.line;
+
+            if last_line != Some(line) {
+                let number = format!("{:>width$}", line, width = gutter_width);
+                out.push_str(&format!("\n{} {} {}", self.paint(BLUE, &number), bar, source));
+                last_line = Some(line)
+            }
+
+            out.push_str(&format!("\n{} {} {}", gutter, bar, self.marker(source, label, mark, mark_style)))
+        }
+
+        if any && (!diagnostic.notes.is_empty() || !diagnostic.suggestions.is_empty()) {
+            out.push_str(&format!("\n{} {}", gutter, bar))
+        }
+
+        for note in diagnostic.notes.iter() {
+            out.push_str(&format!("\n{} {} {}", gutter, self.paint(BLUE, "="), self.paint(BOLD, "note:")));
+            out.push_str(&format!(" {}", note))
+        }
+
+        for suggestion in diagnostic.suggestions.iter() {
+            out.push_str(&format!("\n{} {} {}", gutter, self.paint(BLUE, "="), self.paint(BOLD, "help:")));
+            out.push_str(&format!(" {}", suggestion))
+        }
+
+        out
+    }
+}
@@ -56,6 +56,30 @@ impl SymTab {
         }
     }
 
+    // the closest visible name within a couple of edits, for "did you mean" hints; ties go
+    // to the innermost scope, then to the first name alphabetically, the same every run
+    pub fn similar_name(&self, name: &str) -> Option<String> {
+        let mut best: Option<(usize, String)> = None;
+        let mut current = Some(self);
+
+        while let Some(table) = current {
+            let closest = table.names.borrow().keys()
+                .map(|candidate| (edit_distance(name, candidate), candidate.clone()))
+                .filter(|&(distance, _)| distance <= 2 && distance < name.len())
+                .min();
+
+            if let Some(closest) = closest {
+                if best.as_ref().is_none_or(|b| closest.0 < b.0) {
+                    best = Some(closest)
+                }
+            }
+
+            current = table.parent.as_deref();
+        }
+
+        best.map(|b| b.1)
+    }
+
     pub fn visualize(&self, env_index: usize) {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
@@ -72,13 +96,13 @@ impl SymTab {
     fn dump(&self, f: &mut fmt::Formatter, env_index: usize) -> fmt::Result {
         if env_index > 0 {
             if let Some(ref p) = self.parent {
-                try!(p.dump(f, env_index - 1));
-                try!(writeln!(f, "------------------------------"));
+                p.dump(f, env_index - 1)?;
+                writeln!(f, "------------------------------")?;
             }
         }
 
         for (i, v) in self.names.borrow().iter().enumerate() {
-            try!(writeln!(f, "({} : {}) = {:?}", i, env_index, v))
+            writeln!(f, "({} : {}) = {:?}", i, env_index, v)?
         }
 
         Ok(())
@@ -87,7 +111,31 @@ impl SymTab {
 
 impl fmt::Debug for SymTab {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        try!(self.dump(f, 0));
+        self.dump(f, 0)?;
         Ok(())
     }
 }
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0 ..= b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut last = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let old = row[j + 1];
+
+            row[j + 1] = if ca == *cb {
+                last
+            } else {
+                1 + last.min(row[j]).min(old)
+            };
+
+            last = old;
+        }
+    }
+
+    row[b.len()]
+}